@@ -0,0 +1,196 @@
+use crate::error::Error;
+use clap::ValueEnum;
+use data_encoding::{BASE32, BASE64, BASE64URL, HEXLOWER};
+use rand::Rng;
+use std::io::{self, Write};
+use std::time::Duration;
+
+// Divisible by 3 and 5, so base64 and base32 only pad the final chunk, and by 8, so chunked
+// fills read the same generator output as a single fill
+pub const CHUNK: usize = 120 * 546;
+
+const UNITS: [(&str, u64); 4] = [("G", 1 << 30), ("M", 1 << 20), ("K", 1 << 10), ("", 1)];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Encoding {
+    /// Lowercase hexadecimal
+    #[default]
+    Hex,
+    /// Standard base64 with padding
+    Base64,
+    /// URL safe base64 with padding
+    Base64url,
+    /// RFC 4648 base32 with padding
+    Base32,
+    /// Unencoded bytes
+    Raw,
+}
+
+impl Encoding {
+    fn encoding(&self) -> Option<data_encoding::Encoding> {
+        match self {
+            Encoding::Hex => Some(HEXLOWER),
+            Encoding::Base64 => Some(BASE64),
+            Encoding::Base64url => Some(BASE64URL),
+            Encoding::Base32 => Some(BASE32),
+            Encoding::Raw => None,
+        }
+    }
+    pub fn encode(&self, bytes: &[u8]) -> Option<String> {
+        self.encoding().map(|encoding| encoding.encode(bytes))
+    }
+}
+
+// Byte count with an optional binary suffix, like 32, 64K, 512M or 4G
+pub fn size(value: &str) -> Result<u64, Error> {
+    let invalid = || Error::parameter(format!("invalid size `{}`, expected e.g. 32 or 64K", value));
+    let (number, scale) = UNITS
+        .iter()
+        .find_map(|(suffix, scale)| {
+            value
+                .strip_suffix(suffix)
+                .filter(|number| !number.is_empty())
+                .map(|number| (number, *scale))
+        })
+        .ok_or_else(invalid)?;
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(scale))
+        .ok_or_else(invalid)
+}
+
+pub fn bytes<R: Rng + ?Sized>(rng: &mut R, size: usize) -> Vec<u8> {
+    let mut bytes = vec![0; size];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+// Writes `total` random bytes chunk by chunk, returns the number of bytes written after encoding
+pub fn stream<R: Rng + ?Sized, W: Write>(
+    rng: &mut R,
+    writer: &mut W,
+    total: u64,
+    encoding: Encoding,
+) -> io::Result<u64> {
+    let mut chunk = vec![0; CHUNK];
+    let mut encoded = String::new();
+    let mut remaining = total;
+    let mut written = 0;
+    let encoding = encoding.encoding();
+    while remaining > 0 {
+        let chunk = &mut chunk[..remaining.min(CHUNK as u64) as usize];
+        rng.fill_bytes(chunk);
+        match &encoding {
+            Some(encoding) => {
+                encoded.clear();
+                encoding.encode_append(chunk, &mut encoded);
+                writer.write_all(encoded.as_bytes())?;
+                written += encoded.len() as u64;
+            }
+            None => {
+                writer.write_all(chunk)?;
+                written += chunk.len() as u64;
+            }
+        }
+        remaining -= chunk.len() as u64;
+    }
+    Ok(written)
+}
+
+fn human(bytes: f64) -> String {
+    let (suffix, scale) = UNITS
+        .iter()
+        .find(|(_, scale)| bytes >= *scale as f64)
+        .unwrap_or(&UNITS[3]);
+    if scale == &1 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}iB", bytes / *scale as f64, suffix)
+    }
+}
+
+pub fn throughput(random: u64, written: u64, elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    format!(
+        "wrote {} ({} random) in {:.2}s, {}/s",
+        human(written as f64),
+        human(random as f64),
+        seconds,
+        human((written as f64 / seconds).round())
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(size("32").unwrap(), 32);
+        assert_eq!(size("64K").unwrap(), 65_536);
+        assert_eq!(size("2G").unwrap(), 2 << 30);
+
+        assert!(size("").is_err());
+        assert!(size("K").is_err());
+        assert!(size("1T").is_err());
+        assert!(size("-1").is_err());
+        assert!(size("99999999999G").is_err());
+    }
+
+    #[test]
+    fn encodings() {
+        let bytes = [0xde, 0xad, 0xbe, 0xef];
+
+        assert_eq!(Encoding::Hex.encode(&bytes).unwrap(), "deadbeef");
+        assert_eq!(Encoding::Base64.encode(&[0xfb, 0xff]).unwrap(), "+/8=");
+        assert_eq!(Encoding::Base64url.encode(&[0xfb, 0xff]).unwrap(), "-_8=");
+        assert_eq!(Encoding::Base32.encode(&bytes).unwrap(), "32W353Y=");
+        assert_eq!(Encoding::Raw.encode(&bytes), None);
+    }
+
+    #[test]
+    fn streamed() {
+        let total = CHUNK as u64 * 2 + 7;
+
+        for encoding in [Encoding::Base64, Encoding::Base32, Encoding::Hex] {
+            let mut rng = ChaCha20Rng::seed_from_u64(42);
+            let mut output = Vec::new();
+            let written = stream(&mut rng, &mut output, total, encoding).unwrap();
+            let decoded = encoding.encoding().unwrap().decode(&output).unwrap();
+
+            assert_eq!(written, output.len() as u64);
+            assert_eq!(decoded.len() as u64, total);
+        }
+
+        let mut output = Vec::new();
+        let written = stream(&mut StepRng::new(0, 0), &mut output, 10, Encoding::Raw).unwrap();
+        assert_eq!(written, 10);
+        assert_eq!(output, vec![0; 10]);
+
+        let mut output = Vec::new();
+        stream(
+            &mut Pcg64::seed_from_u64(42),
+            &mut output,
+            total,
+            Encoding::Raw,
+        )
+        .unwrap();
+        assert_eq!(output, bytes(&mut Pcg64::seed_from_u64(42), total as usize));
+    }
+
+    #[test]
+    fn report() {
+        let report = throughput(1 << 30, 1 << 31, Duration::from_secs(2));
+
+        assert_eq!(
+            report,
+            "wrote 2.00 GiB (1.00 GiB random) in 2.00s, 1.00 GiB/s"
+        );
+        assert_eq!(human(512.0), "512 B");
+    }
+}
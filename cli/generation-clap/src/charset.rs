@@ -0,0 +1,171 @@
+use crate::error::Error;
+use crate::password::SYMBOLS;
+use clap::ValueEnum;
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Preset {
+    /// Lowercase hexadecimal digits
+    Hex,
+    /// RFC 4648 base32 alphabet
+    Base32,
+    /// ASCII letters and digits
+    Alnum,
+    /// ASCII punctuation
+    Symbols,
+    /// Russian Cyrillic letters
+    Cyrillic,
+    /// Emoticons block
+    Emoji,
+}
+
+impl Preset {
+    pub fn characters(&self) -> Vec<String> {
+        let spec = match self {
+            Preset::Hex => "0-9a-f",
+            Preset::Base32 => "A-Z2-7",
+            Preset::Alnum => "A-Za-z0-9",
+            Preset::Symbols => return graphemes(SYMBOLS),
+            Preset::Cyrillic => "А-яЁё",
+            Preset::Emoji => "😀-🙏",
+        };
+        parse(spec).expect("preset is valid")
+    }
+}
+
+// Grapheme clusters in order of first appearance, without range syntax
+pub fn graphemes(value: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    value
+        .graphemes(true)
+        .filter(|grapheme| seen.insert(*grapheme))
+        .map(String::from)
+        .collect()
+}
+
+// Next literal grapheme and the position after it, `\` escapes `-` and itself
+fn literal<'a>(graphemes: &[&'a str], position: usize) -> Result<(&'a str, usize), Error> {
+    match graphemes[position] {
+        "\\" => match graphemes.get(position + 1) {
+            Some(grapheme) => Ok((grapheme, position + 2)),
+            None => Err(Error::parameter(
+                "charset ends with an unfinished `\\` escape",
+            )),
+        },
+        grapheme => Ok((grapheme, position + 1)),
+    }
+}
+
+fn scalar(grapheme: &str) -> Result<char, Error> {
+    let mut chars = grapheme.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(Error::parameter(format!(
+            "range bound `{}` is not a single character",
+            grapheme
+        ))),
+    }
+}
+
+// Grapheme clusters with ranges like `a-z0-9`, a `-` at either end is literal
+pub fn parse(spec: &str) -> Result<Vec<String>, Error> {
+    let graphemes = spec.graphemes(true).collect::<Vec<_>>();
+    let mut characters = Vec::new();
+    let mut position = 0;
+    while position < graphemes.len() {
+        let (start, next) = literal(&graphemes, position)?;
+        if graphemes.get(next) == Some(&"-") && next + 1 < graphemes.len() {
+            let (end, after) = literal(&graphemes, next + 1)?;
+            let (first, last) = (scalar(start)?, scalar(end)?);
+            if first > last {
+                return Err(Error::parameter(format!(
+                    "range `{}-{}` is reversed",
+                    first, last
+                )));
+            }
+            characters.extend((first..=last).map(String::from));
+            position = after;
+        } else {
+            characters.push(start.to_string());
+            position = next;
+        }
+    }
+    let mut seen = HashSet::new();
+    characters.retain(|character| seen.insert(character.clone()));
+    Ok(characters)
+}
+
+// Union of the custom characters and presets, minus the exclusions
+pub fn charset(
+    chars: Option<&str>,
+    presets: &[Preset],
+    exclude: Option<&str>,
+) -> Result<Vec<String>, Error> {
+    let mut characters = match chars {
+        Some(chars) => parse(chars)?,
+        None => Vec::new(),
+    };
+    for preset in presets {
+        characters.extend(preset.characters());
+    }
+    let excluded = match exclude {
+        Some(exclude) => parse(exclude)?.into_iter().collect(),
+        None => HashSet::new(),
+    };
+    let mut seen = HashSet::new();
+    characters.retain(|character| !excluded.contains(character) && seen.insert(character.clone()));
+    if characters.is_empty() {
+        return Err(Error::parameter("charset is empty"));
+    }
+    Ok(characters)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn joined(characters: Vec<String>) -> String {
+        characters.concat()
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(joined(parse("a-e0-3").unwrap()), "abcde0123");
+        assert_eq!(joined(parse("-a-c-").unwrap()), "-abc");
+        assert_eq!(joined(parse("a\\-c\\\\").unwrap()), "a-c\\");
+        assert_eq!(joined(parse("aab").unwrap()), "ab");
+        assert_eq!(joined(parse("α-γ").unwrap()), "αβγ");
+
+        assert!(parse("z-a").is_err());
+        assert!(parse("a\\").is_err());
+        assert!(parse("a-👍🏽").is_err());
+    }
+
+    #[test]
+    fn clusters() {
+        let characters = parse("e\u{301}👍🏽🇩🇪").unwrap();
+
+        assert_eq!(characters, vec!["e\u{301}", "👍🏽", "🇩🇪"]);
+        assert_eq!(graphemes("ääb"), vec!["ä", "b"]);
+    }
+
+    #[test]
+    fn presets() {
+        assert_eq!(Preset::Hex.characters().len(), 16);
+        assert_eq!(Preset::Base32.characters().len(), 32);
+        assert_eq!(Preset::Alnum.characters().len(), 62);
+        assert_eq!(Preset::Symbols.characters().len(), 32);
+        assert_eq!(Preset::Cyrillic.characters().len(), 66);
+        assert_eq!(Preset::Emoji.characters().len(), 80);
+    }
+
+    #[test]
+    fn combined() {
+        let characters = charset(Some("xyz"), &[Preset::Hex], Some("0-7x")).unwrap();
+
+        assert_eq!(joined(characters), "yz89abcdef");
+        assert!(charset(None, &[], None).is_err());
+        assert!(charset(Some("ab"), &[], Some("a-b")).is_err());
+    }
+}
@@ -1,13 +1,43 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use generation_clap::bytes::Encoding;
+use generation_clap::charset::Preset;
+use generation_clap::datetime::Style;
+use generation_clap::error::Error;
+use generation_clap::identifier::{Case, Version, NANOID_ALPHABET, NANOID_SIZE};
+use generation_clap::image::{Kind, Ramp};
+use generation_clap::maze::{self, Render};
+use generation_clap::output::Format;
+use generation_clap::passphrase::Capitalization;
+use generation_clap::pattern::MAX_REPEAT;
+use generation_clap::random::Algorithm;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "Random Sample Generator")]
 #[command(author = "Author <name@domain.tld")]
 #[command(version = "1.0")]
 #[command(about = "Generate random integers, floats and strings", long_about = None)]
-pub struct CLI {
+pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    /// Seed for reproducible output (integer)
+    #[arg(long, global = true)]
+    pub seed: Option<u64>,
+    /// Random number generator
+    #[arg(long, value_enum, global = true, default_value_t = Algorithm::Chacha)]
+    pub rng: Algorithm,
+    /// Number of values (integer)
+    #[arg(short = 'n', long, global = true, default_value_t = 1)]
+    pub count: u64,
+    /// Output format
+    #[arg(long, value_enum, global = true, default_value_t = Format::Plain)]
+    pub format: Format,
+    /// Field name for structured output (string)
+    #[arg(long, global = true, default_value = "value")]
+    pub field: String,
+    /// Named subcommand and options from the config file (string)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -29,28 +59,448 @@ pub enum Commands {
     /// Generate random ranged float
     FloatRange {
         /// Start value (float)
-        #[arg(short, long)]
+        #[arg(short, long, allow_negative_numbers = true)]
         start: f64,
         /// End value (float)
-        #[arg(short, long)]
+        #[arg(short, long, allow_negative_numbers = true)]
         end: f64,
+        /// Include the end value (flag)
+        #[arg(short, long)]
+        inclusive: bool,
+        /// Only values on a grid of this step from start (float)
+        #[arg(long)]
+        step: Option<f64>,
+        /// Decimal places (integer)
+        #[arg(short, long, visible_alias = "precision")]
+        decimals: Option<usize>,
     },
     /// Generate random ranged integer
     IntegerRange {
         /// Start value (integer)
-        #[arg(short, long)]
+        #[arg(short, long, allow_negative_numbers = true)]
         start: i64,
         /// End value (integer)
-        #[arg(short, long)]
+        #[arg(short, long, allow_negative_numbers = true)]
         end: i64,
+        /// Include the end value (flag)
+        #[arg(short, long)]
+        inclusive: bool,
+        /// Only values on a grid of this step from start (integer)
+        #[arg(long)]
+        step: Option<i64>,
+        /// Distinct values, --count of them (flag)
+        #[arg(short, long)]
+        unique: bool,
     },
     /// Generate random custom sample string
     Custom {
         /// Length (integer)
         #[arg(short, long)]
+        length: Option<usize>,
+        /// Characters, ranges like a-z0-9 allowed (string)
+        #[arg(short, long)]
+        chars: Option<String>,
+        /// Named character sets, comma separated
+        #[arg(short, long, value_enum, value_delimiter = ',')]
+        preset: Vec<Preset>,
+        /// Characters to leave out, ranges allowed (string)
+        #[arg(short = 'x', long)]
+        exclude: Option<String>,
+    },
+    /// Generate normally distributed float
+    Normal {
+        /// Mean (float)
+        #[arg(short, long, default_value_t = 0.0, allow_negative_numbers = true)]
+        mean: f64,
+        /// Standard deviation (float)
+        #[arg(short, long, default_value_t = 1.0, allow_negative_numbers = true)]
+        stddev: f64,
+    },
+    /// Generate exponentially distributed float
+    Exponential {
+        /// Rate (float)
+        #[arg(short, long, default_value_t = 1.0)]
+        lambda: f64,
+    },
+    /// Generate poisson distributed integer
+    Poisson {
+        /// Mean rate (float)
+        #[arg(short, long, default_value_t = 1.0)]
+        lambda: f64,
+    },
+    /// Generate binomially distributed integer
+    Binomial {
+        /// Number of trials (integer)
+        #[arg(short, long)]
+        trials: u64,
+        /// Success probability (float)
+        #[arg(short, long, default_value_t = 0.5)]
+        probability: f64,
+    },
+    /// Generate log-normally distributed float
+    Lognormal {
+        /// Mean of the underlying normal distribution (float)
+        #[arg(short, long, default_value_t = 0.0, allow_negative_numbers = true)]
+        mean: f64,
+        /// Standard deviation of the underlying normal distribution (float)
+        #[arg(short, long, default_value_t = 1.0)]
+        stddev: f64,
+    },
+    /// Generate bernoulli distributed boolean
+    Bernoulli {
+        /// Success probability (float)
+        #[arg(short, long, default_value_t = 0.5)]
+        probability: f64,
+    },
+    /// Generate weighted choice
+    Weighted {
+        /// Choices (value:weight)
+        choices: Vec<String>,
+        /// File with one value:weight choice per line (path)
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+    },
+    /// Generate UUID
+    Uuid {
+        /// Version
+        #[arg(short, long, value_enum, default_value_t = Version::V4)]
+        version: Version,
+        /// Letter case
+        #[arg(long, value_enum, default_value_t = Case::Lower)]
+        case: Case,
+        /// Without hyphens (flag)
+        #[arg(long)]
+        simple: bool,
+    },
+    /// Generate ULID
+    Ulid {
+        /// Letter case
+        #[arg(long, value_enum, default_value_t = Case::Upper)]
+        case: Case,
+    },
+    /// Generate nanoid
+    Nanoid {
+        /// Size (integer)
+        #[arg(short, long, default_value_t = NANOID_SIZE)]
+        size: usize,
+        /// Alphabet (string)
+        #[arg(short, long, default_value = NANOID_ALPHABET)]
+        alphabet: String,
+    },
+    /// Generate password from the operating system generator (ignores seed)
+    Password {
+        /// Length (integer)
+        #[arg(short, long, default_value_t = 16)]
         length: usize,
-        /// Characters (string)
+        /// Minimum lowercase letters (integer)
+        #[arg(long, default_value_t = 1)]
+        min_lower: usize,
+        /// Minimum uppercase letters (integer)
+        #[arg(long, default_value_t = 1)]
+        min_upper: usize,
+        /// Minimum digits (integer)
+        #[arg(long, default_value_t = 1)]
+        min_digits: usize,
+        /// Minimum symbols (integer)
+        #[arg(long, default_value_t = 1)]
+        min_symbols: usize,
+        /// Exclude ambiguous characters like 0/O and l/1 (flag)
+        #[arg(long)]
+        no_ambiguous: bool,
+        /// Use every character at most once (flag)
+        #[arg(long)]
+        no_repeats: bool,
+        /// Minimum estimated entropy in bits (float)
+        #[arg(long, default_value_t = 64.0)]
+        min_entropy: f64,
+    },
+    /// Generate passphrase from a word list, built in is the BIP39 English list with 2048 words
+    Passphrase {
+        /// Number of words (integer)
+        #[arg(short, long, default_value_t = 6)]
+        words: usize,
+        /// Word list file with one word per line, like the EFF large list (path)
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+        /// Separator (string)
+        #[arg(short, long, default_value = " ")]
+        separator: String,
+        /// Capitalization
+        #[arg(short, long, value_enum, default_value_t = Capitalization::None)]
+        capitalize: Capitalization,
+        /// Number of digits inserted after random words (integer)
+        #[arg(short, long, default_value_t = 0)]
+        digits: usize,
+    },
+    /// Generate records from a schema file
+    Records {
+        /// Schema with field definitions (path)
+        #[arg(short, long)]
+        schema: PathBuf,
+    },
+    /// Generate random date and time
+    Datetime {
+        /// Earliest time, RFC 3339, `now` or offset like -30d (string)
+        #[arg(short, long, default_value = "-365d", allow_hyphen_values = true)]
+        after: String,
+        /// Latest time (exclusive), RFC 3339, `now` or offset like +2h (string)
+        #[arg(short, long, default_value = "now", allow_hyphen_values = true)]
+        before: String,
+        /// Timezone name like Europe/Berlin (string)
+        #[arg(short, long, default_value = "UTC")]
+        timezone: String,
+        /// Output as `rfc3339`, `unix`, `millis` or a strftime pattern (string)
+        #[arg(short, long, default_value = "rfc3339")]
+        pattern: String,
+    },
+    /// Generate random duration
+    Duration {
+        /// Shortest duration like 90s or 1h30m (string)
+        #[arg(long, default_value = "0")]
+        min: String,
+        /// Longest duration (string)
+        #[arg(long, default_value = "1d")]
+        max: String,
+        /// Output style
+        #[arg(short, long, value_enum, default_value_t = Style::Human)]
+        style: Style,
+    },
+    /// Generate random bytes
+    Bytes {
+        /// Bytes per value, suffixes K, M and G allowed (string)
+        #[arg(short, long, default_value = "32")]
+        size: String,
+        /// Encoding
+        #[arg(short, long, value_enum, default_value_t = Encoding::Hex)]
+        encoding: Encoding,
+        /// Stream size times count bytes into a file instead (path)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate string matching a regular expression subset
+    Pattern {
+        /// Pattern with classes, groups, alternation and quantifiers (string)
+        pattern: String,
+        /// Most extra repetitions for *, + and open ranges (integer)
+        #[arg(short, long, default_value_t = MAX_REPEAT)]
+        max_repeat: usize,
+    },
+    /// Render a noise texture to a PNG file
+    Image {
+        /// Width in pixels (integer)
+        #[arg(long, default_value_t = 256)]
+        width: u32,
+        /// Height in pixels (integer)
+        #[arg(long, default_value_t = 256)]
+        height: u32,
+        /// Noise kind
+        #[arg(short, long, value_enum, default_value_t = Kind::Perlin)]
+        kind: Kind,
+        /// Fractal octaves, each at double frequency and half amplitude (integer)
+        #[arg(long, default_value_t = 4)]
+        octaves: u32,
+        /// Lattice cells across the width for the first octave (float)
+        #[arg(short, long, default_value_t = 4.0)]
+        scale: f64,
+        /// Colour ramp
+        #[arg(short, long, value_enum, default_value_t = Ramp::Gray)]
+        ramp: Ramp,
+        /// Output file (path)
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Generate a maze as text, PNG or wall rectangles
+    Maze {
+        /// Width in cells (integer)
+        #[arg(long, default_value_t = 16)]
+        width: usize,
+        /// Height in cells (integer)
+        #[arg(long, default_value_t = 12)]
+        height: usize,
+        /// Carving algorithm
+        #[arg(short, long, value_enum, default_value_t = maze::Algorithm::Backtracker)]
+        algorithm: maze::Algorithm,
+        /// Share of dead ends opened into loops, 0 keeps the maze perfect (float)
+        #[arg(short, long, default_value_t = 0.0)]
+        braid: f64,
+        /// Output style
+        #[arg(short, long, value_enum, default_value_t = Render::Ascii)]
+        render: Render,
+        /// Cell size in pixels for png and rects (integer)
+        #[arg(long, default_value_t = 32)]
+        cell: u32,
+        /// Wall thickness in pixels for png and rects (integer)
+        #[arg(long, default_value_t = 16)]
+        wall: u32,
+        /// Output file, required for png (path)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Roll dice expressions like 3d6+2, 4d6kh3 or 2d10!-1
+    Roll {
+        /// NdM dice with `!` to explode and kh/kl to keep, combined with + - * / and parentheses (string)
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true)]
+        expression: Vec<String>,
+        /// Print the exact distribution, mean and variance instead of rolling (flag)
+        #[arg(short, long)]
+        stats: bool,
+    },
+    /// Generate a timestamped series from trend, seasonality, drift, noise and spikes
+    Series {
+        /// First timestamp, RFC 3339, `now` or offset like -1d (string)
+        #[arg(long, default_value = "-1d", allow_hyphen_values = true)]
+        start: String,
+        /// Time between points like 1m or 1h30m (string)
+        #[arg(short, long, default_value = "1m")]
+        interval: String,
+        /// Base level (float)
+        #[arg(short, long, default_value_t = 100.0, allow_negative_numbers = true)]
+        level: f64,
+        /// Change per point (float)
+        #[arg(short, long, default_value_t = 0.0, allow_negative_numbers = true)]
+        trend: f64,
+        /// Seasonal amplitude (float)
+        #[arg(short, long, default_value_t = 0.0)]
+        amplitude: f64,
+        /// Seasonal period like 1d (string)
+        #[arg(short, long, default_value = "1d")]
+        period: String,
+        /// Random walk step standard deviation (float)
+        #[arg(short, long, default_value_t = 0.0)]
+        walk: f64,
+        /// Gaussian noise standard deviation (float)
+        #[arg(long, default_value_t = 1.0)]
+        noise: f64,
+        /// Spike probability per point (float)
+        #[arg(long, default_value_t = 0.0)]
+        spikes: f64,
+        /// Spike size, scaled by a random factor between 1 and 2 (float)
+        #[arg(long, default_value_t = 50.0, allow_negative_numbers = true)]
+        spike: f64,
+        /// Decimal places (integer)
+        #[arg(short, long)]
+        decimals: Option<usize>,
+        /// Timestamp as `rfc3339`, `unix`, `millis` or a strftime pattern (string)
+        #[arg(long, default_value = "rfc3339")]
+        pattern: String,
+    },
+    /// Shuffle input lines
+    Shuffle {
+        /// Input files, stdin when none or `-` (path)
+        files: Vec<PathBuf>,
+    },
+    /// Sample input lines without replacement, in input order
+    Sample {
+        /// Sample size (integer)
+        #[arg(short = 'k', long)]
+        size: usize,
+        /// Input files, stdin when none or `-` (path)
+        files: Vec<PathBuf>,
+        /// Column holding the line weight, starting at 1 (integer)
+        #[arg(short, long)]
+        weights: Option<usize>,
+        /// Column delimiter (string)
+        #[arg(short, long, default_value = "\t")]
+        delimiter: String,
+    },
+    /// Pick random input lines with replacement, once per count
+    Pick {
+        /// Input files, stdin when none or `-` (path)
+        files: Vec<PathBuf>,
+        /// Column holding the line weight, starting at 1 (integer)
         #[arg(short, long)]
-        chars: String,
+        weights: Option<usize>,
+        /// Column delimiter (string)
+        #[arg(short, long, default_value = "\t")]
+        delimiter: String,
     },
+    /// Serve the generators over a local HTTP API, like GET /string?length=16&count=3
+    Serve {
+        /// Port to listen on (integer)
+        #[arg(short, long, default_value_t = 8080)]
+        port: u16,
+        /// Address to bind (string)
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        /// Largest count a request may ask for (integer)
+        #[arg(long, default_value_t = 10_000)]
+        max_count: u64,
+    },
+    /// Inspect the config file and environment settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Run statistical checks on the configured generator
+    Test {
+        /// Number of samples per check (integer)
+        #[arg(long, default_value_t = 100_000)]
+        samples: u64,
+        /// Range start value (integer)
+        #[arg(short, long, default_value_t = 0, allow_negative_numbers = true)]
+        start: i64,
+        /// Range end value (integer)
+        #[arg(short, long, default_value_t = 100, allow_negative_numbers = true)]
+        end: i64,
+        /// Histogram buckets (integer)
+        #[arg(short, long, default_value_t = 10)]
+        buckets: usize,
+        /// Custom string length (integer)
+        #[arg(short, long)]
+        length: Option<usize>,
+        /// Custom characters, ranges like a-z0-9 allowed (string)
+        #[arg(short, long)]
+        chars: Option<String>,
+        /// Significance level, checks with a lower p-value fail (float)
+        #[arg(short, long, default_value_t = 0.01)]
+        alpha: f64,
+        /// Render histograms in the terminal (flag)
+        #[arg(long)]
+        histogram: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the effective settings and where they come from
+    Show,
+}
+
+// Command line for a subcommand and `key=value` options, flags take `true`, `false` or no value
+pub fn arguments(
+    name: &str,
+    options: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<String>, Error> {
+    let cli = Cli::command();
+    let command = cli
+        .find_subcommand(name)
+        .filter(|_| !name.is_empty())
+        .ok_or_else(|| Error::parameter(format!("unknown command `{}`", name)))?;
+
+    let mut arguments = vec![name.to_string()];
+    let mut positionals = Vec::new();
+    for (key, value) in options {
+        let key = key.replace('_', "-");
+        let arg = command
+            .get_arguments()
+            .chain(cli.get_arguments())
+            .find(|arg| arg.get_long() == Some(key.as_str()) || arg.get_id() == key.as_str())
+            .ok_or_else(|| Error::parameter(format!("unknown parameter `{}`", key)))?;
+        if arg.is_positional() {
+            positionals.push(value);
+        } else if arg.get_action().takes_values() {
+            arguments.push(format!("--{}={}", key, value));
+        } else {
+            match value.as_str() {
+                "" | "true" => arguments.push(format!("--{}", key)),
+                "false" => {}
+                _ => return Err(Error::parameter(format!("`{}` takes true or false", key))),
+            }
+        }
+    }
+    if !positionals.is_empty() {
+        arguments.push(String::from("--"));
+        arguments.extend(positionals);
+    }
+    Ok(arguments)
 }
@@ -0,0 +1,435 @@
+use crate::charset;
+use crate::error::Error;
+use crate::generation::{CHARACTERS, LENGTH};
+use crate::output::Format;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const FILE: &str = "generation-clap.toml";
+// Environment variables are this prefix plus the upper case key, like GENERATION_CLAP_LENGTH
+pub const PREFIX: &str = "GENERATION_CLAP_";
+
+// Seed used when --seed is not given
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Seed {
+    // Fresh entropy on every run
+    #[default]
+    Random,
+    // Fresh entropy, printed to stderr so the run can be repeated
+    Report,
+    Fixed(u64),
+}
+
+impl FromStr for Seed {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Error> {
+        match value {
+            "random" => Ok(Seed::Random),
+            "report" => Ok(Seed::Report),
+            value => value.parse().map(Seed::Fixed).map_err(|_| {
+                Error::parameter(format!(
+                    "seed `{}` is not random, report or an integer",
+                    value
+                ))
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Seed::Random => write!(f, "\"random\""),
+            Seed::Report => write!(f, "\"report\""),
+            Seed::Fixed(seed) => write!(f, "{}", seed),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Source {
+    Default,
+    File,
+    Environment,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            source: Source::Default,
+        }
+    }
+    fn set(&mut self, value: Option<T>, source: Source) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = source;
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Layer {
+    length: Option<usize>,
+    charset: Option<String>,
+    format: Option<String>,
+    seed: Option<toml::Value>,
+    #[serde(default)]
+    profiles: BTreeMap<String, toml::Table>,
+}
+
+#[derive(Serialize)]
+struct Profiles<'a> {
+    profiles: &'a BTreeMap<String, toml::Table>,
+}
+
+// Defaults from the config file, overridden by environment variables, overridden by flags
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub path: Option<PathBuf>,
+    pub length: Setting<usize>,
+    pub charset: Setting<String>,
+    pub format: Setting<Format>,
+    pub seed: Setting<Seed>,
+    // Subcommand under `command` and its options, see `profile`
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+fn format(value: &str) -> Result<Format, Error> {
+    Format::from_str(value, true).map_err(|_| {
+        Error::parameter(format!(
+            "format `{}` is not plain, json, ndjson or csv",
+            value
+        ))
+    })
+}
+
+fn seed(value: toml::Value) -> Result<Seed, Error> {
+    match value {
+        toml::Value::Integer(seed) => u64::try_from(seed)
+            .map(Seed::Fixed)
+            .map_err(|_| Error::parameter("seed must not be negative")),
+        toml::Value::String(seed) => seed.parse(),
+        value => Err(Error::parameter(format!(
+            "seed `{}` is not random, report or an integer",
+            value
+        ))),
+    }
+}
+
+// Config directory file unless GENERATION_CLAP_CONFIG points elsewhere
+pub fn path(environment: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    environment(&format!("{}CONFIG", PREFIX))
+        .map(PathBuf::from)
+        .or_else(|| dirs::config_dir().map(|directory| directory.join(FILE)))
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            length: Setting::new(LENGTH),
+            charset: Setting::new(CHARACTERS.to_string()),
+            format: Setting::new(Format::default()),
+            seed: Setting::new(Seed::default()),
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    pub fn load() -> Result<Self, Error> {
+        let environment = |key: &str| std::env::var(key).ok();
+        let mut settings = Self::new();
+        settings.path = path(environment);
+        if let Some(path) = settings.path.take() {
+            match fs::read_to_string(&path) {
+                Ok(file) => {
+                    settings.file(&file).map_err(|error| match error {
+                        Error::Parameter(message) => {
+                            Error::parameter(format!("{}: {}", path.display(), message))
+                        }
+                        error => error,
+                    })?;
+                    settings.path = Some(path);
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        settings.environment(environment)?;
+        settings.check()?;
+        Ok(settings)
+    }
+
+    // Layers the config file contents and the environment over the defaults
+    pub fn merge(
+        file: Option<&str>,
+        environment: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, Error> {
+        let mut settings = Self::new();
+        if let Some(file) = file {
+            settings.file(file)?;
+        }
+        settings.environment(environment)?;
+        settings.check()?;
+        Ok(settings)
+    }
+
+    fn file(&mut self, file: &str) -> Result<(), Error> {
+        let layer: Layer =
+            toml::from_str(file).map_err(|error| Error::parameter(error.message()))?;
+        self.length.set(layer.length, Source::File);
+        self.charset.set(layer.charset, Source::File);
+        self.format.set(
+            layer.format.as_deref().map(format).transpose()?,
+            Source::File,
+        );
+        self.seed
+            .set(layer.seed.map(seed).transpose()?, Source::File);
+        self.profiles = layer.profiles;
+        Ok(())
+    }
+
+    fn environment(&mut self, environment: impl Fn(&str) -> Option<String>) -> Result<(), Error> {
+        let variable = |key: &str| {
+            let name = format!("{}{}", PREFIX, key.to_uppercase());
+            environment(&name).map(|value| (name, value))
+        };
+        let invalid = |name: &str, error: Error| match error {
+            Error::Parameter(message) => Error::parameter(format!("{}: {}", name, message)),
+            error => error,
+        };
+        if let Some((name, length)) = variable("length") {
+            let length = length
+                .parse()
+                .map_err(|_| Error::parameter(format!("{} must be an integer", name)))?;
+            self.length.set(Some(length), Source::Environment);
+        }
+        if let Some((_, charset)) = variable("charset") {
+            self.charset.set(Some(charset), Source::Environment);
+        }
+        if let Some((name, value)) = variable("format") {
+            let value = format(&value).map_err(|error| invalid(&name, error))?;
+            self.format.set(Some(value), Source::Environment);
+        }
+        if let Some((name, value)) = variable("seed") {
+            let value = value.parse().map_err(|error| invalid(&name, error))?;
+            self.seed.set(Some(value), Source::Environment);
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if charset::parse(&self.charset.value)?.is_empty() {
+            return Err(Error::parameter("charset is empty"));
+        }
+        Ok(())
+    }
+
+    // Subcommand and option pairs of a profile, arrays repeat their option
+    pub fn profile(&self, name: &str) -> Result<(String, Vec<(String, String)>), Error> {
+        let invalid = |reason: &str| Error::parameter(format!("profile `{}`: {}", name, reason));
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| invalid("not found in the config file"))?;
+        let command = match profile.get("command") {
+            Some(toml::Value::String(command)) => command.clone(),
+            _ => return Err(invalid("needs a `command` string")),
+        };
+        let mut options = Vec::new();
+        for (key, value) in profile.iter().filter(|(key, _)| *key != "command") {
+            let values = match value {
+                toml::Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                let value = match value {
+                    toml::Value::String(value) => value.clone(),
+                    toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+                        value.to_string()
+                    }
+                    _ => {
+                        return Err(invalid(&format!(
+                            "`{}` must be a string, number or bool",
+                            key
+                        )))
+                    }
+                };
+                options.push((key.clone(), value));
+            }
+        }
+        Ok((command, options))
+    }
+
+    // Effective settings as TOML, commented with where each value comes from
+    pub fn show<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match &self.path {
+            Some(path) => writeln!(writer, "# config file: {}", path.display())?,
+            None => writeln!(writer, "# config file: none")?,
+        }
+        let source = |source: Source| match source {
+            Source::Default => "default",
+            Source::File => "file",
+            Source::Environment => "environment",
+        };
+        let format = self
+            .format
+            .value
+            .to_possible_value()
+            .expect("formats are not skipped");
+        let lines = [
+            ("length", self.length.value.to_string(), self.length.source),
+            (
+                "charset",
+                toml::Value::from(self.charset.value.as_str()).to_string(),
+                self.charset.source,
+            ),
+            (
+                "format",
+                format!("\"{}\"", format.get_name()),
+                self.format.source,
+            ),
+            ("seed", self.seed.value.to_string(), self.seed.source),
+        ];
+        for (key, value, from) in lines {
+            writeln!(writer, "{} = {}  # {}", key, value, source(from))?;
+        }
+        if !self.profiles.is_empty() {
+            let profiles = toml::to_string(&Profiles {
+                profiles: &self.profiles,
+            })
+            .map_err(io::Error::other)?;
+            write!(writer, "\n{}", profiles)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILE: &str = r#"
+length = 24
+charset = "a-f0-9"
+format = "json"
+seed = 42
+
+[profiles.api-keys]
+command = "custom"
+length = 40
+preset = ["alnum", "hex"]
+count = 3
+"#;
+
+    fn environment<'a>(variables: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            variables
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    #[test]
+    fn layers() {
+        let defaults = Settings::merge(None, environment(&[])).unwrap();
+        assert_eq!(defaults, Settings::new());
+
+        let file = Settings::merge(Some(FILE), environment(&[])).unwrap();
+        assert_eq!(file.length.value, 24);
+        assert_eq!(file.format.value, Format::Json);
+        assert_eq!(file.seed.value, Seed::Fixed(42));
+        assert_eq!(file.seed.source, Source::File);
+
+        let variables = [
+            ("GENERATION_CLAP_LENGTH", "8"),
+            ("GENERATION_CLAP_SEED", "report"),
+        ];
+        let both = Settings::merge(Some(FILE), environment(&variables)).unwrap();
+        assert_eq!(both.length.value, 8);
+        assert_eq!(both.length.source, Source::Environment);
+        assert_eq!(both.seed.value, Seed::Report);
+        assert_eq!(both.charset.value, "a-f0-9");
+    }
+
+    #[test]
+    fn profiles() {
+        let settings = Settings::merge(Some(FILE), environment(&[])).unwrap();
+        let (command, options) = settings.profile("api-keys").unwrap();
+
+        assert_eq!(command, "custom");
+        assert_eq!(
+            options,
+            vec![
+                ("count".to_string(), "3".to_string()),
+                ("length".to_string(), "40".to_string()),
+                ("preset".to_string(), "alnum".to_string()),
+                ("preset".to_string(), "hex".to_string()),
+            ]
+        );
+        assert!(settings.profile("missing").is_err());
+    }
+
+    #[test]
+    fn shown() {
+        let mut settings = Settings::merge(Some(FILE), environment(&[])).unwrap();
+        settings.path = Some(PathBuf::from("/tmp/generation-clap.toml"));
+        let mut output = Vec::new();
+        settings.show(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("# config file: /tmp/generation-clap.toml\n"));
+        assert!(output.contains("length = 24  # file\n"));
+        assert!(output.contains("format = \"json\"  # file\n"));
+        assert!(output.contains("seed = 42  # file\n"));
+        assert!(output.contains("[profiles.api-keys]\n"));
+
+        let shown = output
+            .lines()
+            .map(|line| line.split("  #").next().unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(toml::from_str::<toml::Table>(&shown).is_ok());
+    }
+
+    #[test]
+    fn invalid() {
+        for file in [
+            "length = -1",
+            "format = \"xml\"",
+            "seed = \"sometimes\"",
+            "seed = -4",
+            "charset = \"\"",
+            "colour = \"red\"",
+        ] {
+            assert!(
+                Settings::merge(Some(file), environment(&[])).is_err(),
+                "{}",
+                file
+            );
+        }
+        let variables = [("GENERATION_CLAP_LENGTH", "long")];
+        assert!(Settings::merge(None, environment(&variables)).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::error::Error;
+use crate::output::Value;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, SecondsFormat, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use rand::Rng;
+use std::fmt::Write;
+
+const UNITS: [(&str, i64); 6] = [
+    ("ms", 1),
+    ("s", 1_000),
+    ("m", 60_000),
+    ("h", 3_600_000),
+    ("d", 86_400_000),
+    ("w", 604_800_000),
+];
+
+// Compound durations like `1h30m` or `250ms`, a bare `0` is also accepted
+pub fn duration(value: &str) -> Result<TimeDelta, Error> {
+    let invalid = || {
+        Error::parameter(format!(
+            "invalid duration `{}`, expected e.g. `1h30m`",
+            value
+        ))
+    };
+    if value == "0" {
+        return Ok(TimeDelta::zero());
+    }
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    let mut rest = value;
+    let mut millis = 0i64;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let amount = rest[..digits].parse::<i64>().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest, |end| &rest[..end]);
+        let (_, scale) = UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .ok_or_else(invalid)?;
+        millis = amount
+            .checked_mul(*scale)
+            .and_then(|amount| millis.checked_add(amount))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit.len()..];
+    }
+    TimeDelta::try_milliseconds(millis).ok_or_else(invalid)
+}
+
+// RFC 3339, `now` or an offset from now like `-30d` or `+2h`
+pub fn instant(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
+    let offset = |delta: Result<TimeDelta, Error>, sign: i32| {
+        delta.and_then(|delta| {
+            now.checked_add_signed(delta * sign)
+                .ok_or_else(|| Error::parameter(format!("`{}` is out of range", value)))
+        })
+    };
+    if value == "now" {
+        Ok(now)
+    } else if let Some(delta) = value.strip_prefix('-') {
+        offset(duration(delta), -1)
+    } else if let Some(delta) = value.strip_prefix('+') {
+        offset(duration(delta), 1)
+    } else {
+        DateTime::parse_from_rfc3339(value)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .map_err(|_| {
+                Error::parameter(format!(
+                    "invalid time `{}`, expected RFC 3339, `now` or an offset like `-30d`",
+                    value
+                ))
+            })
+    }
+}
+
+pub fn timezone(name: &str) -> Result<Tz, Error> {
+    name.parse::<Tz>()
+        .map_err(|_| Error::parameter(format!("unknown timezone `{}`", name)))
+}
+
+// Uniform over whole milliseconds in [after, before)
+pub fn between<R: Rng + ?Sized>(
+    rng: &mut R,
+    after: DateTime<Utc>,
+    before: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let millis = rng.gen_range(after.timestamp_millis()..before.timestamp_millis());
+    DateTime::from_timestamp_millis(millis).expect("between two valid times")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Layout {
+    Rfc3339,
+    Unix,
+    Millis,
+    Pattern(String),
+}
+
+impl Layout {
+    // `rfc3339`, `unix` and `millis` are keywords, anything else is a strftime pattern
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        match pattern {
+            "rfc3339" => Ok(Layout::Rfc3339),
+            "unix" => Ok(Layout::Unix),
+            "millis" => Ok(Layout::Millis),
+            pattern => {
+                // Parse-only specifiers like `%#z` only fail once something is formatted
+                let mut trial = String::new();
+                if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error))
+                    || write!(trial, "{}", DateTime::UNIX_EPOCH.format(pattern)).is_err()
+                {
+                    return Err(Error::parameter(format!(
+                        "invalid strftime pattern `{}`",
+                        pattern
+                    )));
+                }
+                Ok(Layout::Pattern(pattern.into()))
+            }
+        }
+    }
+    pub fn value<T: TimeZone>(&self, datetime: DateTime<T>) -> Value
+    where
+        T::Offset: std::fmt::Display,
+    {
+        match self {
+            Layout::Rfc3339 => datetime.to_rfc3339_opts(SecondsFormat::Millis, true).into(),
+            Layout::Unix => datetime.timestamp().into(),
+            Layout::Millis => datetime.timestamp_millis().into(),
+            Layout::Pattern(pattern) => datetime.format(pattern).to_string().into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Style {
+    /// Units from weeks down to milliseconds, like 1h 2m 3s
+    #[default]
+    Human,
+    /// ISO 8601, like PT1H2M3S
+    Iso,
+    /// Whole seconds
+    Seconds,
+    /// Whole milliseconds
+    Millis,
+}
+
+fn human(millis: i64) -> String {
+    let mut rest = millis;
+    let mut parts = Vec::new();
+    for (name, scale) in UNITS.iter().rev() {
+        if rest >= *scale {
+            parts.push(format!("{}{}", rest / scale, name));
+            rest %= scale;
+        }
+    }
+    if parts.is_empty() {
+        "0s".into()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn iso(millis: i64) -> String {
+    let (days, rest) = (millis / 86_400_000, millis % 86_400_000);
+    let (hours, rest) = (rest / 3_600_000, rest % 3_600_000);
+    let (minutes, rest) = (rest / 60_000, rest % 60_000);
+    let (seconds, millis) = (rest / 1_000, rest % 1_000);
+
+    let mut value = String::from("P");
+    if days > 0 {
+        value += &format!("{}D", days);
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || millis > 0 || days == 0 {
+        value.push('T');
+        if hours > 0 {
+            value += &format!("{}H", hours);
+        }
+        if minutes > 0 {
+            value += &format!("{}M", minutes);
+        }
+        if millis > 0 {
+            value += &format!("{}.{:03}S", seconds, millis);
+        } else if seconds > 0 || (hours == 0 && minutes == 0) {
+            value += &format!("{}S", seconds);
+        }
+    }
+    value
+}
+
+impl Style {
+    pub fn value(&self, duration: TimeDelta) -> Value {
+        let millis = duration.num_milliseconds();
+        match self {
+            Style::Human => human(millis).into(),
+            Style::Iso => iso(millis).into(),
+            Style::Seconds => duration.num_seconds().into(),
+            Style::Millis => millis.into(),
+        }
+    }
+}
+
+// Uniform over whole milliseconds in [min, max]
+pub fn interval<R: Rng + ?Sized>(rng: &mut R, min: TimeDelta, max: TimeDelta) -> TimeDelta {
+    TimeDelta::milliseconds(rng.gen_range(min.num_milliseconds()..=max.num_milliseconds()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(duration("1h30m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(duration("2w1d").unwrap(), TimeDelta::days(15));
+        assert_eq!(duration("250ms").unwrap(), TimeDelta::milliseconds(250));
+        assert_eq!(duration("0").unwrap(), TimeDelta::zero());
+
+        assert!(duration("").is_err());
+        assert!(duration("10").is_err());
+        assert!(duration("5y").is_err());
+        assert!(duration("h").is_err());
+        assert!(duration("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn instants() {
+        assert_eq!(instant("now", now()).unwrap(), now());
+        assert_eq!(
+            instant("-30d", now()).unwrap(),
+            Utc.with_ymd_and_hms(2024, 5, 2, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            instant("+1h", now()).unwrap(),
+            Utc.with_ymd_and_hms(2024, 6, 1, 13, 0, 0).unwrap()
+        );
+        assert_eq!(
+            instant("2024-01-01T02:00:00+02:00", now()).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
+        assert!(instant("yesterday", now()).is_err());
+    }
+
+    #[test]
+    fn layouts() {
+        let datetime = now().with_timezone(&timezone("Europe/Berlin").unwrap());
+
+        assert_eq!(
+            Layout::new("rfc3339").unwrap().value(datetime),
+            Value::String("2024-06-01T14:00:00.000+02:00".into())
+        );
+        assert_eq!(
+            Layout::new("unix").unwrap().value(datetime),
+            Value::Integer(1_717_243_200)
+        );
+        assert_eq!(
+            Layout::new("%d.%m.%Y %H:%M").unwrap().value(datetime),
+            Value::String("01.06.2024 14:00".into())
+        );
+        assert!(Layout::new("%Q").is_err());
+        assert!(Layout::new("%#z").is_err());
+        assert!(timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn styles() {
+        let duration = TimeDelta::milliseconds(93_784_005);
+
+        assert_eq!(
+            Style::Human.value(duration),
+            Value::String("1d 2h 3m 4s 5ms".into())
+        );
+        assert_eq!(
+            Style::Iso.value(duration),
+            Value::String("P1DT2H3M4.005S".into())
+        );
+        assert_eq!(Style::Seconds.value(duration), Value::Integer(93_784));
+        assert_eq!(
+            Style::Iso.value(TimeDelta::zero()),
+            Value::String("PT0S".into())
+        );
+        assert_eq!(
+            Style::Iso.value(TimeDelta::days(2)),
+            Value::String("P2D".into())
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = StepRng::new(0, 0);
+        let after = now() - TimeDelta::days(1);
+
+        assert_eq!(between(&mut rng, after, now()), after);
+        assert_eq!(
+            interval(&mut rng, TimeDelta::seconds(5), TimeDelta::seconds(10)),
+            TimeDelta::seconds(5)
+        );
+    }
+}
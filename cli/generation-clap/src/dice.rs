@@ -0,0 +1,777 @@
+use crate::error::Error;
+use crate::generation::range;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+pub const MAX_DICE: u64 = 1_000;
+pub const MAX_SIDES: u64 = 1_000_000;
+// Exploding dice reroll at most this often, rolls and distributions share the cap
+pub const MAX_EXPLOSIONS: usize = 10;
+
+// Limits for exact distributions, outcomes per distribution and steps per operation
+const MAX_OUTCOMES: usize = 1_000_000;
+const MAX_WORK: u64 = 100_000_000;
+
+const WIDTH: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Keep {
+    Highest(u64),
+    Lowest(u64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Dice {
+    count: u64,
+    sides: u64,
+    explode: bool,
+    keep: Option<Keep>,
+}
+
+impl Dice {
+    fn kept(&self) -> u64 {
+        match self.keep {
+            Some(Keep::Highest(kept) | Keep::Lowest(kept)) => kept,
+            None => self.count,
+        }
+    }
+    fn largest(&self) -> u64 {
+        if self.explode {
+            self.sides * (MAX_EXPLOSIONS as u64 + 1)
+        } else {
+            self.sides
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+        }
+    }
+    // Division rounds down
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide => {
+                let quotient = left.checked_div(right)?;
+                if left % right != 0 && (left < 0) != (right < 0) {
+                    Some(quotient - 1)
+                } else {
+                    Some(quotient)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(i64),
+    Dice(Dice),
+    Negate(Box<Node>),
+    Group(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    // Smallest and largest result, fails when any intermediate value overflows
+    fn bounds(&self) -> Option<(i64, i64)> {
+        match self {
+            Node::Number(value) => Some((*value, *value)),
+            Node::Dice(dice) => {
+                let kept = dice.kept() as i64;
+                Some((kept, kept.checked_mul(dice.largest() as i64)?))
+            }
+            Node::Negate(node) => {
+                let (min, max) = node.bounds()?;
+                Some((max.checked_neg()?, min.checked_neg()?))
+            }
+            Node::Group(node) => node.bounds(),
+            Node::Binary(operator, left, right) => {
+                let (left, right) = (left.bounds()?, right.bounds()?);
+                let corners = [
+                    operator.apply(left.0, right.0)?,
+                    operator.apply(left.0, right.1)?,
+                    operator.apply(left.1, right.0)?,
+                    operator.apply(left.1, right.1)?,
+                ];
+                Some((*corners.iter().min()?, *corners.iter().max()?))
+            }
+        }
+    }
+
+    fn roll<R: Rng + ?Sized>(&self, rng: &mut R, detail: &mut String) -> i64 {
+        match self {
+            Node::Number(value) => {
+                detail.push_str(&value.to_string());
+                *value
+            }
+            Node::Dice(dice) => roll(dice, rng, detail),
+            Node::Negate(node) => {
+                detail.push('-');
+                -node.roll(rng, detail)
+            }
+            Node::Group(node) => {
+                detail.push('(');
+                let value = node.roll(rng, detail);
+                detail.push(')');
+                value
+            }
+            Node::Binary(operator, left, right) => {
+                let left = left.roll(rng, detail);
+                detail.push_str(&format!(" {} ", operator.symbol()));
+                let right = right.roll(rng, detail);
+                operator.apply(left, right).expect("bounds are checked")
+            }
+        }
+    }
+
+    fn distribution(&self) -> Result<Distribution, Error> {
+        match self {
+            Node::Number(value) => Ok(Distribution::constant(*value)),
+            Node::Dice(dice) => distribution(dice),
+            Node::Negate(node) => Ok(node.distribution()?.negate()),
+            Node::Group(node) => node.distribution(),
+            Node::Binary(operator, left, right) => left
+                .distribution()?
+                .combine(&right.distribution()?, *operator),
+        }
+    }
+}
+
+// Dice are shown in rolled order, `!` joins exploded rerolls and `~` marks dropped dice
+fn roll<R: Rng + ?Sized>(dice: &Dice, rng: &mut R, detail: &mut String) -> i64 {
+    let rolls = (0..dice.count)
+        .map(|_| {
+            let mut rolls = vec![range(rng, 1, dice.sides as i64 + 1)];
+            while dice.explode
+                && rolls.last() == Some(&(dice.sides as i64))
+                && rolls.len() <= MAX_EXPLOSIONS
+            {
+                rolls.push(range(rng, 1, dice.sides as i64 + 1));
+            }
+            rolls
+        })
+        .collect::<Vec<_>>();
+
+    let mut order = (0..rolls.len()).collect::<Vec<_>>();
+    order.sort_by_key(|index| rolls[*index].iter().sum::<i64>());
+    let kept = match dice.keep {
+        Some(Keep::Highest(kept)) => order.split_off(order.len() - kept as usize),
+        Some(Keep::Lowest(kept)) => order[..kept as usize].to_vec(),
+        None => order,
+    };
+
+    let mut total = 0;
+    let shown = rolls
+        .iter()
+        .enumerate()
+        .map(|(index, rolls)| {
+            let value = rolls
+                .iter()
+                .map(|roll| roll.to_string())
+                .collect::<Vec<_>>()
+                .join("!");
+            if kept.contains(&index) {
+                total += rolls.iter().sum::<i64>();
+                value
+            } else {
+                format!("~{}", value)
+            }
+        })
+        .collect::<Vec<_>>();
+    detail.push_str(&format!("[{}]", shown.join(", ")));
+    total
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> Error {
+        Error::parameter(format!("dice: {} at position {}", message, self.position))
+    }
+    fn peek(&mut self) -> Option<char> {
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|c| c.is_whitespace())
+        {
+            self.position += 1;
+        }
+        self.chars.get(self.position).copied()
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+    // Modifiers follow the dice directly, without whitespace
+    fn follows(&mut self, c: char) -> bool {
+        if self.chars.get(self.position) == Some(&c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expression(&mut self) -> Result<Node, Error> {
+        let mut node = self.product()?;
+        loop {
+            let operator = match self.peek() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Subtract,
+                _ => return Ok(node),
+            };
+            self.position += 1;
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, Error> {
+        let mut node = self.unary()?;
+        loop {
+            let operator = match self.peek() {
+                Some('*') => Operator::Multiply,
+                Some('/') => Operator::Divide,
+                _ => return Ok(node),
+            };
+            self.position += 1;
+            let start = self.position;
+            let right = self.unary()?;
+            if operator == Operator::Divide {
+                match right {
+                    Node::Number(0) => {
+                        self.position = start;
+                        return Err(self.error("division by zero"));
+                    }
+                    Node::Number(_) => {}
+                    _ => {
+                        self.position = start;
+                        return Err(self.error("can only divide by a number"));
+                    }
+                }
+            }
+            node = Node::Binary(operator, Box::new(node), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, Error> {
+        if self.eat('-') {
+            return Ok(match self.unary()? {
+                Node::Number(value) => Node::Number(-value),
+                node => Node::Negate(Box::new(node)),
+            });
+        }
+        self.eat('+');
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Node, Error> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let node = self.expression()?;
+                if !self.eat(')') {
+                    return Err(self.error("expected `)` to close the group"));
+                }
+                Ok(Node::Group(Box::new(node)))
+            }
+            Some('d' | 'D') => self.dice(1),
+            Some(c) if c.is_ascii_digit() => {
+                let number = self.number()?;
+                if matches!(self.chars.get(self.position), Some('d' | 'D')) {
+                    self.dice(number)
+                } else {
+                    i64::try_from(number)
+                        .map(Node::Number)
+                        .map_err(|_| self.error("number is too large"))
+                }
+            }
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, Error> {
+        let start = self.position;
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_digit())
+        {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.error("expected a number"));
+        }
+        self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .map_err(|_| self.error("number is too large"))
+    }
+
+    // `NdM`, `d%`, then optional `!` to explode and `k`, `kh` or `kl` to keep
+    fn dice(&mut self, count: u64) -> Result<Node, Error> {
+        if !(1..=MAX_DICE).contains(&count) {
+            return Err(self.error(&format!("dice count must be between 1 and {}", MAX_DICE)));
+        }
+        self.position += 1;
+        let sides = if self.follows('%') {
+            100
+        } else {
+            self.number()?
+        };
+        if !(1..=MAX_SIDES).contains(&sides) {
+            return Err(self.error(&format!("sides must be between 1 and {}", MAX_SIDES)));
+        }
+        let explode = self.follows('!');
+        if explode && sides == 1 {
+            return Err(self.error("exploding dice need at least 2 sides"));
+        }
+        let keep = if self.follows('k') {
+            let keep: fn(u64) -> Keep = if self.follows('l') {
+                Keep::Lowest
+            } else {
+                self.follows('h');
+                Keep::Highest
+            };
+            let kept = self.number()?;
+            if !(1..=count).contains(&kept) {
+                return Err(self.error(&format!("can keep between 1 and {} dice", count)));
+            }
+            Some(keep(kept))
+        } else {
+            None
+        };
+        Ok(Node::Dice(Dice {
+            count,
+            sides,
+            explode,
+            keep,
+        }))
+    }
+}
+
+// Exact probabilities of every possible result
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution {
+    outcomes: BTreeMap<i64, f64>,
+}
+
+impl Distribution {
+    fn constant(value: i64) -> Self {
+        Self {
+            outcomes: BTreeMap::from([(value, 1.0)]),
+        }
+    }
+    fn negate(&self) -> Self {
+        Self {
+            outcomes: self
+                .outcomes
+                .iter()
+                .map(|(value, p)| (-value, *p))
+                .collect(),
+        }
+    }
+    // Smallest outcome and probabilities of every value from there on
+    fn dense(&self) -> Option<(i64, Vec<f64>)> {
+        let (first, last) = (
+            *self.outcomes.first_key_value()?.0,
+            *self.outcomes.last_key_value()?.0,
+        );
+        let span = usize::try_from(last.checked_sub(first)?).ok()? + 1;
+        if span > MAX_OUTCOMES {
+            return None;
+        }
+        let mut probabilities = vec![0.0; span];
+        for (value, p) in &self.outcomes {
+            probabilities[(value - first) as usize] = *p;
+        }
+        Some((first, probabilities))
+    }
+    fn combine(&self, other: &Self, operator: Operator) -> Result<Self, Error> {
+        if operator == Operator::Add {
+            if let (Some((left, p)), Some((right, q))) = (self.dense(), other.dense()) {
+                work(p.len() as u64 * q.len() as u64)?;
+                let mut sums = vec![0.0; p.len() + q.len() - 1];
+                for (i, p) in p.iter().enumerate() {
+                    for (j, q) in q.iter().enumerate() {
+                        sums[i + j] += p * q;
+                    }
+                }
+                let outcomes = sums
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, p)| *p > 0.0)
+                    .map(|(offset, p)| (left + right + offset as i64, p))
+                    .collect::<BTreeMap<_, _>>();
+                outcomes_limit(outcomes.len())?;
+                return Ok(Self { outcomes });
+            }
+        }
+        work(self.outcomes.len() as u64 * other.outcomes.len() as u64)?;
+        let mut outcomes = BTreeMap::new();
+        for (left, p) in &self.outcomes {
+            for (right, q) in &other.outcomes {
+                let value = operator.apply(*left, *right).expect("bounds are checked");
+                *outcomes.entry(value).or_insert(0.0) += p * q;
+            }
+        }
+        outcomes_limit(outcomes.len())?;
+        Ok(Self { outcomes })
+    }
+    pub fn outcomes(&self) -> impl Iterator<Item = (i64, f64)> + '_ {
+        self.outcomes.iter().map(|(value, p)| (*value, *p))
+    }
+    pub fn mean(&self) -> f64 {
+        self.outcomes().map(|(value, p)| value as f64 * p).sum()
+    }
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.outcomes()
+            .map(|(value, p)| (value as f64 - mean).powi(2) * p)
+            .sum()
+    }
+}
+
+fn work(steps: u64) -> Result<(), Error> {
+    if steps > MAX_WORK {
+        return Err(Error::parameter("expression is too large for --stats"));
+    }
+    Ok(())
+}
+
+fn outcomes_limit(outcomes: usize) -> Result<(), Error> {
+    if outcomes > MAX_OUTCOMES {
+        return Err(Error::parameter("expression is too large for --stats"));
+    }
+    Ok(())
+}
+
+fn die(sides: u64, explode: bool) -> Result<Distribution, Error> {
+    let rolls = if explode { MAX_EXPLOSIONS + 1 } else { 1 };
+    outcomes_limit(usize::try_from(sides).map_or(usize::MAX, |sides| sides.saturating_mul(rolls)))?;
+    let face = 1.0 / sides as f64;
+    let sides = sides as i64;
+    if !explode {
+        return Ok(Distribution {
+            outcomes: (1..=sides).map(|value| (value, face)).collect(),
+        });
+    }
+    // `explosions` maximum rolls followed by a final roll, which never explodes at the cap
+    let mut outcomes = BTreeMap::new();
+    for explosions in 0..=MAX_EXPLOSIONS as i32 {
+        let last = if explosions == MAX_EXPLOSIONS as i32 {
+            sides
+        } else {
+            sides - 1
+        };
+        let p = face.powi(explosions + 1);
+        for value in 1..=last {
+            outcomes.insert(explosions as i64 * sides + value, p);
+        }
+    }
+    Ok(Distribution { outcomes })
+}
+
+// Probabilities of 0 to `count` successes
+fn binomial(count: u64, p: f64) -> Vec<f64> {
+    let mut coefficient = 1.0;
+    (0..=count)
+        .map(|successes| {
+            let chance =
+                coefficient * p.powi(successes as i32) * (1.0 - p).powi((count - successes) as i32);
+            coefficient *= (count - successes) as f64 / (successes + 1) as f64;
+            chance
+        })
+        .collect()
+}
+
+// Sum of the `kept` highest of `count` dice, assigning faces from the highest down
+fn highest(die: &Distribution, count: u64, kept: u64) -> Result<Distribution, Error> {
+    let largest = die
+        .outcomes
+        .keys()
+        .map(|value| value.abs())
+        .max()
+        .unwrap_or(0);
+    work(
+        (die.outcomes.len() as u64)
+            .saturating_mul(kept)
+            .saturating_mul(count + 1)
+            .saturating_mul(kept.saturating_mul(largest as u64) + 1),
+    )?;
+    let faces = die.outcomes().collect::<Vec<_>>();
+    let below = faces
+        .iter()
+        .scan(0.0, |total, (_, p)| {
+            *total += p;
+            Some(*total)
+        })
+        .collect::<Vec<_>>();
+
+    // `states[assigned]` holds the kept sums while fewer than `kept` dice are assigned
+    let mut states = vec![BTreeMap::new(); kept as usize];
+    states[0].insert(0, 1.0);
+    let mut finished = BTreeMap::new();
+    for (index, (value, p)) in faces.iter().enumerate().rev() {
+        // Chance of showing this face given it is the highest face left
+        let p = if index == 0 { 1.0 } else { p / below[index] };
+        let mut next = vec![BTreeMap::new(); kept as usize];
+        for (assigned, sums) in states.iter().enumerate() {
+            let chances = binomial(count - assigned as u64, p);
+            for (sum, q) in sums {
+                for (shown, chance) in chances.iter().enumerate() {
+                    if *chance == 0.0 {
+                        continue;
+                    }
+                    let total = assigned + shown;
+                    let sum = sum + shown.min(kept as usize - assigned) as i64 * value;
+                    let target = if total >= kept as usize {
+                        &mut finished
+                    } else {
+                        &mut next[total]
+                    };
+                    *target.entry(sum).or_insert(0.0) += q * chance;
+                }
+            }
+        }
+        states = next;
+    }
+    outcomes_limit(finished.len())?;
+    Ok(Distribution { outcomes: finished })
+}
+
+fn distribution(dice: &Dice) -> Result<Distribution, Error> {
+    let die = die(dice.sides, dice.explode)?;
+    match dice.keep {
+        Some(Keep::Highest(kept)) if kept < dice.count => highest(&die, dice.count, kept),
+        Some(Keep::Lowest(kept)) if kept < dice.count => {
+            Ok(highest(&die.negate(), dice.count, kept)?.negate())
+        }
+        _ => {
+            // Each convolution grows the running sum by one die
+            let faces = die.outcomes.len() as u64;
+            let sums = dice.count.saturating_mul(faces);
+            work((dice.count - 1).saturating_mul(faces).saturating_mul(sums) / 2)?;
+            let mut total = die.clone();
+            for _ in 1..dice.count {
+                total = total.combine(&die, Operator::Add)?;
+            }
+            Ok(total)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Roll {
+    pub total: i64,
+    // The expression with every dice term replaced by its rolls
+    pub detail: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    node: Node,
+}
+
+impl Expression {
+    pub fn new(source: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+        };
+        let node = parser.expression()?;
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(&format!("unexpected `{}`", c)));
+        }
+        if node.bounds().is_none() {
+            return Err(Error::parameter("dice: expression can overflow"));
+        }
+        Ok(Self { node })
+    }
+    pub fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> Roll {
+        let mut detail = String::new();
+        let total = self.node.roll(rng, &mut detail);
+        Roll { total, detail }
+    }
+    pub fn distribution(&self) -> Result<Distribution, Error> {
+        self.node.distribution()
+    }
+}
+
+pub fn report<W: Write>(writer: &mut W, distribution: &Distribution) -> io::Result<()> {
+    let variance = distribution.variance();
+    writeln!(writer, "mean     {:.4}", distribution.mean())?;
+    writeln!(writer, "variance {:.4}", variance)?;
+    writeln!(writer, "stddev   {:.4}", variance.sqrt())?;
+    let label = distribution
+        .outcomes()
+        .map(|(value, _)| value.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let maximum = distribution
+        .outcomes()
+        .map(|(_, p)| p)
+        .fold(f64::MIN_POSITIVE, f64::max);
+    for (value, p) in distribution.outcomes() {
+        let bar = (p / maximum * WIDTH as f64).round() as usize;
+        writeln!(
+            writer,
+            "  {:>label$} {:>9.5}% {}",
+            value,
+            p * 100.0,
+            "#".repeat(bar)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn distribution(source: &str) -> Distribution {
+        Expression::new(source).unwrap().distribution().unwrap()
+    }
+
+    fn close(left: f64, right: f64) -> bool {
+        (left - right).abs() < 1e-9
+    }
+
+    #[test]
+    fn rolls() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let expression = Expression::new("3d6 + 2").unwrap();
+
+        for _ in 0..64 {
+            let roll = expression.roll(&mut rng);
+            let (dice, modifier) = roll.detail.split_once(" + ").unwrap();
+            let sum = dice
+                .trim_matches(['[', ']'])
+                .split(", ")
+                .map(|die| die.parse::<i64>().unwrap())
+                .sum::<i64>();
+
+            assert_eq!(modifier, "2");
+            assert_eq!(roll.total, sum + 2);
+            assert!((5..=20).contains(&roll.total));
+        }
+    }
+
+    #[test]
+    fn modifiers() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let keep = Expression::new("4d6kh3").unwrap();
+        let explode = Expression::new("2d2!").unwrap();
+
+        for _ in 0..64 {
+            let roll = keep.roll(&mut rng);
+            assert_eq!(roll.detail.matches('~').count(), 1);
+            assert!((3..=18).contains(&roll.total));
+
+            let roll = explode.roll(&mut rng);
+            assert!((2..=2 * 2 * (MAX_EXPLOSIONS as i64 + 1)).contains(&roll.total));
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+
+        assert_eq!(
+            Expression::new("2*(3+4)-7/2").unwrap().roll(&mut rng).total,
+            11
+        );
+        assert_eq!(Expression::new("-7/2").unwrap().roll(&mut rng).total, -4);
+        assert_eq!(
+            Expression::new("(1 + 2) * -3")
+                .unwrap()
+                .roll(&mut rng)
+                .detail,
+            "(1 + 2) * -3"
+        );
+    }
+
+    #[test]
+    fn distributions() {
+        let sum = distribution("3d6+2");
+        assert!(close(sum.mean(), 12.5));
+        assert!(close(sum.variance(), 8.75));
+        assert_eq!(sum.outcomes().next(), Some((5, 1.0 / 216.0)));
+
+        let highest = distribution("2d20kh1");
+        assert!(close(highest.mean(), 13.825));
+        let lowest = distribution("2d20kl1");
+        assert!(close(lowest.mean(), 7.175));
+
+        // 4d6 drop lowest, mean known to be 15869/1296
+        assert!(close(distribution("4d6k3").mean(), 15_869.0 / 1_296.0));
+
+        let explode = distribution("d6!");
+        assert!(close(explode.outcomes().map(|(_, p)| p).sum(), 1.0));
+        assert_eq!(explode.outcomes().find(|(value, _)| *value == 6), None);
+        assert!(close(
+            explode.outcomes().find(|(value, _)| *value == 7).unwrap().1,
+            1.0 / 36.0
+        ));
+
+        let percent = distribution("d% / 10");
+        assert_eq!(percent.outcomes().last(), Some((10, 0.01)));
+
+        assert_eq!(distribution("d100000").outcomes().count(), 100_000);
+        assert!(Expression::new("d100000!").unwrap().distribution().is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        for source in [
+            "",
+            "3d",
+            "0d6",
+            "d0",
+            "4d6kh5",
+            "4d6k0",
+            "d1!",
+            "2d6 / d4",
+            "1/0",
+            "(1+2",
+            "3d6)",
+            "3x",
+            "99999999999999999999",
+            "9223372036854775807*2",
+        ] {
+            assert!(Expression::new(source).is_err(), "{}", source);
+        }
+        assert!(Expression::new("1000d1000000")
+            .unwrap()
+            .distribution()
+            .is_err());
+    }
+}
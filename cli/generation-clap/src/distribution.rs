@@ -0,0 +1,188 @@
+use crate::error::Error;
+use rand_distr::{
+    Bernoulli, Binomial, Exp, LogNormal, Normal, Poisson, WeightedError, WeightedIndex,
+};
+use std::fs;
+use std::path::Path;
+
+fn finite(name: &str, value: f64) -> Result<f64, Error> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(Error::parameter(format!(
+            "{} must be a finite number",
+            name
+        )))
+    }
+}
+
+pub fn normal(mean: f64, stddev: f64) -> Result<Normal<f64>, Error> {
+    finite("mean", mean)?;
+    if finite("stddev", stddev)? < 0.0 {
+        return Err(Error::parameter("stddev must not be negative"));
+    }
+    Normal::new(mean, stddev).map_err(|error| Error::parameter(error.to_string()))
+}
+
+pub fn lognormal(mean: f64, stddev: f64) -> Result<LogNormal<f64>, Error> {
+    finite("mean", mean)?;
+    if finite("stddev", stddev)? < 0.0 {
+        return Err(Error::parameter("stddev must not be negative"));
+    }
+    LogNormal::new(mean, stddev).map_err(|error| Error::parameter(error.to_string()))
+}
+
+pub fn exponential(lambda: f64) -> Result<Exp<f64>, Error> {
+    if finite("lambda", lambda)? <= 0.0 {
+        return Err(Error::parameter("lambda must be greater than 0"));
+    }
+    Exp::new(lambda).map_err(|error| Error::parameter(error.to_string()))
+}
+
+// Samples stay far below u64::MAX, so printing them as integers is exact
+pub const MAX_LAMBDA: f64 = 1e15;
+
+pub fn poisson(lambda: f64) -> Result<Poisson<f64>, Error> {
+    if finite("lambda", lambda)? <= 0.0 {
+        return Err(Error::parameter("lambda must be greater than 0"));
+    }
+    if lambda > MAX_LAMBDA {
+        return Err(Error::parameter(format!(
+            "lambda must not be above {:e}",
+            MAX_LAMBDA
+        )));
+    }
+    Poisson::new(lambda).map_err(|error| Error::parameter(error.to_string()))
+}
+
+pub fn binomial(trials: u64, probability: f64) -> Result<Binomial, Error> {
+    // rand_distr counts trials in an i64 internally
+    if trials > i64::MAX as u64 {
+        return Err(Error::parameter(format!(
+            "trials must not be above {}",
+            i64::MAX
+        )));
+    }
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(Error::parameter("probability must be between 0 and 1"));
+    }
+    Binomial::new(trials, probability).map_err(|error| Error::parameter(error.to_string()))
+}
+
+pub fn bernoulli(probability: f64) -> Result<Bernoulli, Error> {
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(Error::parameter("probability must be between 0 and 1"));
+    }
+    Bernoulli::new(probability).map_err(|error| Error::parameter(error.to_string()))
+}
+
+// Weights that sum to infinity would panic inside `WeightedIndex`
+pub fn index(weights: &[f64]) -> Result<WeightedIndex<f64>, Error> {
+    if !weights.iter().sum::<f64>().is_finite() {
+        return Err(Error::parameter("weights must have a finite sum"));
+    }
+    WeightedIndex::new(weights).map_err(|error| match error {
+        WeightedError::AllWeightsZero => Error::parameter("at least one weight must be above 0"),
+        error => Error::parameter(error.to_string()),
+    })
+}
+
+pub struct Weighted {
+    pub values: Vec<String>,
+    pub index: WeightedIndex<f64>,
+}
+
+fn entry(entry: &str) -> Result<(String, f64), Error> {
+    let (value, weight) = entry
+        .rsplit_once(':')
+        .ok_or_else(|| Error::parameter(format!("expected `value:weight`, found `{}`", entry)))?;
+    let weight = weight
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| Error::parameter(format!("invalid weight `{}` for `{}`", weight, value)))?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(Error::parameter(format!(
+            "weight for `{}` must be a finite number of at least 0",
+            value
+        )));
+    }
+    Ok((value.into(), weight))
+}
+
+// Entries are `value:weight` pairs, a file holds one entry per line
+pub fn weighted(entries: &[String], file: Option<&Path>) -> Result<Weighted, Error> {
+    let mut pairs = entries
+        .iter()
+        .map(|value| entry(value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(file) = file {
+        let content = fs::read_to_string(file)?;
+        for line in content.lines().map(str::trim) {
+            if !line.is_empty() && !line.starts_with('#') {
+                pairs.push(entry(line)?);
+            }
+        }
+    }
+
+    if pairs.is_empty() {
+        return Err(Error::parameter("no `value:weight` entries given"));
+    }
+    let (values, weights): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+
+    Ok(Weighted {
+        values,
+        index: index(&weights)?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+    use rand::Rng;
+
+    #[test]
+    fn parameters() {
+        assert!(normal(0.0, 1.0).is_ok());
+        assert!(normal(0.0, -1.0).is_err());
+        assert!(normal(f64::NAN, 1.0).is_err());
+        assert!(lognormal(0.0, f64::INFINITY).is_err());
+        assert!(exponential(0.0).is_err());
+        assert!(poisson(-1.0).is_err());
+        assert!(poisson(1e30).is_err());
+        assert!(poisson(MAX_LAMBDA).is_ok());
+        assert!(binomial(10, 1.5).is_err());
+        assert!(binomial(u64::MAX, 0.5).is_err());
+        assert!(binomial(i64::MAX as u64, 0.5).is_ok());
+        assert!(bernoulli(-0.1).is_err());
+        assert!(bernoulli(1.0).is_ok());
+    }
+
+    #[test]
+    fn weighted_entries() {
+        let weighted = weighted(&["a:1".into(), "b:c:3".into()], None).unwrap();
+
+        assert_eq!(weighted.values, vec!["a", "b:c"]);
+    }
+
+    #[test]
+    fn weighted_invalid() {
+        assert!(weighted(&[], None).is_err());
+        assert!(weighted(&["a".into()], None).is_err());
+        assert!(weighted(&["a:x".into()], None).is_err());
+        assert!(weighted(&["a:-1".into()], None).is_err());
+        assert!(weighted(&["a:0".into(), "b:0".into()], None).is_err());
+        assert!(weighted(&["a:1e308".into(), "b:1e308".into()], None).is_err());
+    }
+
+    #[test]
+    fn weighted_zero() {
+        let weighted = weighted(&["a:0".into(), "b:1".into()], None).unwrap();
+        let mut rng = StepRng::new(0, 1 << 60);
+
+        for _ in 0..16 {
+            assert_eq!(weighted.values[rng.sample(&weighted.index)], "b");
+        }
+    }
+}
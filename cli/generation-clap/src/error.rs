@@ -0,0 +1,40 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    Parameter(String),
+    Check(String),
+    Io(io::Error),
+}
+
+impl Error {
+    pub fn parameter(message: impl Into<String>) -> Self {
+        Error::Parameter(message.into())
+    }
+    pub fn code(&self) -> u8 {
+        match self {
+            Error::Parameter(_) => 2,
+            Error::Check(_) => 3,
+            Error::Io(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parameter(message) => write!(f, "invalid parameter: {}", message),
+            Error::Check(message) => write!(f, "{}", message),
+            Error::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -1,37 +1,410 @@
-use rand::distributions::{uniform::SampleUniform, Alphanumeric, DistString};
-use rand::Rng;
+use crate::charset;
+use crate::error::Error;
+use rand::distributions::{self, uniform::SampleUniform, DistString};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
 pub const LENGTH: usize = 32;
 
-pub const CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
-                                abcdefghijklmnopqrstuvwxyz\
-                                0123456789)(*&^%$#@!~";
+pub const CHARACTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                              abcdefghijklmnopqrstuvwxyz\
+                              0123456789)(*&^%$#@!~";
 
-pub fn float() -> f64 {
-    rand::thread_rng().gen::<f64>()
+pub fn float<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    rng.gen::<f64>()
 }
 
-pub fn signed() -> i32 {
-    rand::thread_rng().gen::<i32>()
+pub fn signed<R: Rng + ?Sized>(rng: &mut R) -> i32 {
+    rng.gen::<i32>()
 }
 
-pub fn unsigned() -> u32 {
-    rand::thread_rng().gen::<u32>()
+pub fn unsigned<R: Rng + ?Sized>(rng: &mut R) -> u32 {
+    rng.gen::<u32>()
 }
 
-pub fn string(length: usize) -> String {
-    Alphanumeric.sample_string(&mut rand::thread_rng(), length)
+pub fn string<R: Rng + ?Sized>(rng: &mut R, length: usize) -> String {
+    distributions::Alphanumeric.sample_string(rng, length)
 }
 
-pub fn range<T: SampleUniform + PartialOrd>(start: T, end: T) -> T {
-    rand::thread_rng().gen_range(start..end)
+pub fn range<R: Rng + ?Sized, T: SampleUniform + PartialOrd>(rng: &mut R, start: T, end: T) -> T {
+    rng.gen_range(start..end)
 }
 
-pub fn custom(length: usize, characters: &[u8]) -> String {
+// Rounds to `decimals` places, so floats print without sampling noise
+pub fn round(value: f64, decimals: usize) -> f64 {
+    format!("{:.*}", decimals, value)
+        .parse::<f64>()
+        .unwrap_or(value)
+}
+
+// `amount` distinct values of `0..length` in random order, Floyd's algorithm keeps memory at `amount`
+pub fn distinct<R: Rng + ?Sized>(rng: &mut R, length: u64, amount: u64) -> Vec<u64> {
+    let amount = amount.min(length);
+    let mut seen = HashSet::new();
+    let mut values = Vec::new();
+    for last in length - amount..length {
+        let value = rng.gen_range(0..=last);
+        let value = if seen.contains(&value) { last } else { value };
+        seen.insert(value);
+        values.push(value);
+    }
+    values.shuffle(rng);
+    values
+}
+
+pub fn custom<R: Rng + ?Sized, S: AsRef<str>>(
+    rng: &mut R,
+    length: usize,
+    characters: &[S],
+) -> String {
     (0..length)
         .map(|_| {
-            let idx = rand::thread_rng().gen_range(0..characters.len());
-            characters[idx] as char
+            let idx = rng.gen_range(0..characters.len());
+            characters[idx].as_ref()
         })
         .collect()
 }
+
+pub trait Generator {
+    type Output;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> Self::Output;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Float;
+
+impl Float {
+    pub fn new() -> Self {
+        Float
+    }
+}
+
+impl Default for Float {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Generator for Float {
+    type Output = f64;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> f64 {
+        float(rng)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alphanumeric {
+    length: usize,
+}
+
+impl Alphanumeric {
+    pub fn new(length: usize) -> Self {
+        Self { length }
+    }
+}
+
+impl Default for Alphanumeric {
+    fn default() -> Self {
+        Self::new(LENGTH)
+    }
+}
+
+impl Generator for Alphanumeric {
+    type Output = String;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> String {
+        string(rng, self.length)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Custom {
+    length: usize,
+    characters: Vec<String>,
+}
+
+impl Custom {
+    // Characters are grapheme clusters, see `charset` for building them
+    pub fn new(length: usize, characters: Vec<String>) -> Result<Self, Error> {
+        if characters.is_empty() {
+            return Err(Error::parameter("charset is empty"));
+        }
+        Ok(Self { length, characters })
+    }
+}
+
+impl Default for Custom {
+    fn default() -> Self {
+        Self {
+            length: LENGTH,
+            characters: charset::graphemes(CHARACTERS),
+        }
+    }
+}
+
+impl Generator for Custom {
+    type Output = String;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> String {
+        custom(rng, self.length, &self.characters)
+    }
+}
+
+// Bounds rand can sample between, float bounds must not overflow their span
+pub trait Bound: SampleUniform + PartialOrd + Copy {
+    fn spannable(_start: Self, _end: Self) -> bool {
+        true
+    }
+}
+
+impl Bound for i32 {}
+impl Bound for i64 {}
+impl Bound for u32 {}
+impl Bound for u64 {}
+
+impl Bound for f32 {
+    fn spannable(start: Self, end: Self) -> bool {
+        (end - start).is_finite()
+    }
+}
+
+impl Bound for f64 {
+    fn spannable(start: Self, end: Self) -> bool {
+        (end - start).is_finite()
+    }
+}
+
+// Half open or inclusive range, checked up front so generation never panics
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Range<T> {
+    start: T,
+    end: T,
+    inclusive: bool,
+}
+
+impl<T: Bound> Range<T> {
+    pub fn new(start: T, end: T) -> Result<Self, Error> {
+        if start.partial_cmp(&end) != Some(Ordering::Less) {
+            return Err(Error::parameter("start must be below end"));
+        }
+        if !T::spannable(start, end) {
+            return Err(Error::parameter("range is too wide"));
+        }
+        Ok(Self {
+            start,
+            end,
+            inclusive: false,
+        })
+    }
+    pub fn inclusive(start: T, end: T) -> Result<Self, Error> {
+        if !matches!(
+            start.partial_cmp(&end),
+            Some(Ordering::Less | Ordering::Equal)
+        ) {
+            return Err(Error::parameter("start must not be above end"));
+        }
+        if !T::spannable(start, end) {
+            return Err(Error::parameter("range is too wide"));
+        }
+        Ok(Self {
+            start,
+            end,
+            inclusive: true,
+        })
+    }
+}
+
+impl<T: Bound> Generator for Range<T> {
+    type Output = T;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> T {
+        if self.inclusive {
+            rng.gen_range(self.start..=self.end)
+        } else {
+            range(rng, self.start, self.end)
+        }
+    }
+}
+
+// Points `start + k * step` inside a range, see `Range::step`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid<T> {
+    start: T,
+    step: T,
+    points: u64,
+}
+
+impl<T> Grid<T> {
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+}
+
+impl Range<i64> {
+    // Only the points `start + k * step` of the range
+    pub fn step(self, step: i64) -> Result<Grid<i64>, Error> {
+        if step <= 0 {
+            return Err(Error::parameter("step must be above 0"));
+        }
+        let (span, stride) = (self.end as i128 - self.start as i128, step as i128);
+        let points = if self.inclusive {
+            span / stride + 1
+        } else {
+            (span + stride - 1) / stride
+        };
+        Ok(Grid {
+            start: self.start,
+            step,
+            points: u64::try_from(points)
+                .map_err(|_| Error::parameter("range has too many steps"))?,
+        })
+    }
+}
+
+impl Grid<i64> {
+    pub fn point(&self, index: u64) -> i64 {
+        (self.start as i128 + index as i128 * self.step as i128) as i64
+    }
+    // At most `points()` distinct values in random order
+    pub fn distinct<R: Rng + ?Sized>(&self, rng: &mut R, amount: u64) -> Vec<i64> {
+        distinct(rng, self.points, amount)
+            .into_iter()
+            .map(|index| self.point(index))
+            .collect()
+    }
+}
+
+impl Range<f64> {
+    pub fn step(self, step: f64) -> Result<Grid<f64>, Error> {
+        if !(step.is_finite() && step > 0.0) {
+            return Err(Error::parameter("step must be a finite number above 0"));
+        }
+        // Tolerates rounding in spans like 1.0 / 0.1
+        let steps = (self.end - self.start) / step;
+        let points = if self.inclusive {
+            (steps + 1e-9).floor() + 1.0
+        } else {
+            (steps - 1e-9).ceil().max(1.0)
+        };
+        if points >= u64::MAX as f64 {
+            return Err(Error::parameter("range has too many steps"));
+        }
+        Ok(Grid {
+            start: self.start,
+            step,
+            points: points as u64,
+        })
+    }
+}
+
+impl Grid<f64> {
+    pub fn point(&self, index: u64) -> f64 {
+        self.start + index as f64 * self.step
+    }
+}
+
+impl Generator for Grid<i64> {
+    type Output = i64;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> i64 {
+        self.point(rng.gen_range(0..self.points))
+    }
+}
+
+impl Generator for Grid<f64> {
+    type Output = f64;
+
+    fn generate<R: RngCore + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.point(rng.gen_range(0..self.points))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn generators() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+
+        assert_eq!(Alphanumeric::new(12).generate(&mut rng).len(), 12);
+        assert!((0.0..1.0).contains(&Float::new().generate(&mut rng)));
+        assert!((-3..3).contains(&Range::new(-3, 3).unwrap().generate(&mut rng)));
+        assert_eq!(Custom::default().generate(&mut rng).chars().count(), LENGTH);
+
+        let custom = Custom::new(4, vec!["ä".into(), "👍🏽".into()]).unwrap();
+        assert!(custom.generate(&mut rng).chars().all(|c| "ä👍🏽".contains(c)));
+    }
+
+    #[test]
+    fn dynamic() {
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha20Rng::seed_from_u64(42));
+        let mut again = ChaCha20Rng::seed_from_u64(42);
+
+        assert_eq!(
+            Alphanumeric::default().generate(&mut *rng),
+            Alphanumeric::default().generate(&mut again)
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+
+        assert_eq!(Range::inclusive(5, 5).unwrap().generate(&mut rng), 5);
+        assert_eq!(round(0.1 + 0.2, 2), 0.3);
+
+        let grid = Range::inclusive(-10, 10).unwrap().step(5).unwrap();
+        assert_eq!(grid.points(), 5);
+        assert!((0..64).all(|_| [-10, -5, 0, 5, 10].contains(&grid.generate(&mut rng))));
+        assert_eq!(Range::new(0, 10).unwrap().step(3).unwrap().points(), 4);
+        assert_eq!(Range::new(0, 9).unwrap().step(3).unwrap().points(), 3);
+
+        let grid = Range::inclusive(0.0, 1.0).unwrap().step(0.1).unwrap();
+        assert_eq!(grid.points(), 11);
+        assert_eq!(
+            Range::new(0.0, 1.0).unwrap().step(0.1).unwrap().points(),
+            10
+        );
+    }
+
+    #[test]
+    fn unique() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let grid = Range::inclusive(1, 20).unwrap().step(1).unwrap();
+
+        let mut values = grid.distinct(&mut rng, 20);
+        values.sort_unstable();
+        assert_eq!(values, (1..=20).collect::<Vec<_>>());
+        assert_eq!(grid.distinct(&mut rng, 50).len(), 20);
+
+        let mut again = ChaCha20Rng::seed_from_u64(7);
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        assert_eq!(
+            distinct(&mut rng, 1 << 40, 8),
+            distinct(&mut again, 1 << 40, 8)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Range::new(3, 3).is_err());
+        assert!(Range::new(5, 3).is_err());
+        assert!(Range::inclusive(5, 3).is_err());
+        assert!(Range::new(1.0, f64::NAN).is_err());
+        assert!(Range::new(-f64::MAX, f64::MAX).is_err());
+        assert!(Range::new(0, 10).unwrap().step(0).is_err());
+        assert!(Range::new(0.0, 1.0).unwrap().step(-0.5).is_err());
+        assert!(Range::inclusive(i64::MIN, i64::MAX)
+            .unwrap()
+            .step(1)
+            .is_err());
+        assert!(Custom::new(4, Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::error::Error;
+use clap::ValueEnum;
+use rand::Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+use ulid::Ulid;
+use uuid::Builder;
+
+pub const NANOID_SIZE: usize = 21;
+
+pub const NANOID_ALPHABET: &str =
+    "_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Version {
+    /// Random
+    #[default]
+    #[value(name = "4")]
+    V4,
+    /// Unix timestamp and random
+    #[value(name = "7")]
+    V7,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Case {
+    Lower,
+    Upper,
+}
+
+fn millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+fn cased(value: String, case: Case) -> String {
+    match case {
+        Case::Lower => value.to_lowercase(),
+        Case::Upper => value.to_uppercase(),
+    }
+}
+
+pub fn uuid<R: Rng + ?Sized>(rng: &mut R, version: Version, case: Case, hyphens: bool) -> String {
+    let uuid = match version {
+        Version::V4 => Builder::from_random_bytes(rng.gen()).into_uuid(),
+        Version::V7 => Builder::from_unix_timestamp_millis(millis(), &rng.gen()).into_uuid(),
+    };
+    let value = if hyphens {
+        uuid.hyphenated().to_string()
+    } else {
+        uuid.simple().to_string()
+    };
+    cased(value, case)
+}
+
+pub fn ulid<R: Rng + ?Sized>(rng: &mut R, case: Case) -> String {
+    let random = rng.gen::<u128>() & ((1 << 80) - 1);
+    cased(Ulid::from_parts(millis(), random).to_string(), case)
+}
+
+// Duplicates are dropped so every character is equally likely
+pub fn alphabet(alphabet: &str) -> Result<Vec<char>, Error> {
+    let mut characters = Vec::new();
+    for c in alphabet.chars() {
+        if !characters.contains(&c) {
+            characters.push(c);
+        }
+    }
+    if characters.is_empty() {
+        return Err(Error::parameter("alphabet must not be empty"));
+    }
+    Ok(characters)
+}
+
+pub fn nanoid<R: Rng + ?Sized>(rng: &mut R, size: usize, alphabet: &[char]) -> String {
+    (0..size)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    #[test]
+    fn uuid_format() {
+        let mut rng = StepRng::new(u64::MAX, 0);
+
+        let value = uuid(&mut rng, Version::V4, Case::Lower, true);
+        assert_eq!(value, "ffffffff-ffff-4fff-bfff-ffffffffffff");
+
+        let value = uuid(&mut rng, Version::V4, Case::Upper, false);
+        assert_eq!(value, "FFFFFFFFFFFF4FFFBFFFFFFFFFFFFFFF");
+
+        let value = uuid(&mut rng, Version::V7, Case::Lower, true);
+        assert_eq!(&value[14..15], "7");
+    }
+
+    #[test]
+    fn ulid_format() {
+        let mut rng = StepRng::new(0, 0);
+        let value = ulid(&mut rng, Case::Upper);
+
+        assert_eq!(value.len(), 26);
+        assert!(value.ends_with("0000000000000000"));
+        assert!(value
+            .chars()
+            .all(|c| "0123456789ABCDEFGHJKMNPQRSTVWXYZ".contains(c)));
+
+        let value = ulid(&mut rng, Case::Lower);
+        assert_eq!(value.len(), 26);
+        assert!(value
+            .chars()
+            .all(|c| "0123456789abcdefghjkmnpqrstvwxyz".contains(c)));
+    }
+
+    #[test]
+    fn nanoid_alphabet() {
+        let mut rng = StepRng::new(0, 1 << 62);
+        let alphabet = alphabet("äbä").unwrap();
+
+        assert_eq!(alphabet, vec!['ä', 'b']);
+
+        assert_eq!(nanoid(&mut rng, 4, &alphabet).chars().count(), 4);
+        assert!(nanoid(&mut rng, 8, &alphabet)
+            .chars()
+            .all(|c| c == 'ä' || c == 'b'));
+        assert!(super::alphabet("").is_err());
+    }
+}
@@ -0,0 +1,388 @@
+use crate::error::Error;
+use clap::ValueEnum;
+use png::{BitDepth, ColorType, Encoder};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{self, Write};
+
+pub const MAX_SIZE: u32 = 16_384;
+pub const MAX_OCTAVES: u32 = 16;
+
+const PERSISTENCE: f64 = 0.5;
+const LACUNARITY: f64 = 2.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Kind {
+    /// Independent value per pixel
+    White,
+    /// Interpolated random lattice values
+    Value,
+    /// Gradient noise on a square lattice
+    #[default]
+    Perlin,
+    /// Gradient noise on a triangular lattice
+    Simplex,
+    /// Distance to the nearest random feature point
+    Worley,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Ramp {
+    /// 8-bit grayscale, suited for heightmaps
+    #[default]
+    Gray,
+    /// Water, sand, grass, rock and snow
+    Terrain,
+    /// Black through red and yellow to white
+    Heat,
+    /// Deep to shallow blue
+    Ocean,
+}
+
+impl Ramp {
+    fn stops(&self) -> &'static [(f64, [u8; 3])] {
+        match self {
+            Ramp::Gray => &[(0.0, [0, 0, 0]), (1.0, [255, 255, 255])],
+            Ramp::Terrain => &[
+                (0.0, [20, 40, 120]),
+                (0.4, [40, 90, 180]),
+                (0.45, [220, 205, 150]),
+                (0.55, [70, 150, 60]),
+                (0.75, [110, 100, 90]),
+                (0.9, [245, 245, 245]),
+                (1.0, [255, 255, 255]),
+            ],
+            Ramp::Heat => &[
+                (0.0, [0, 0, 0]),
+                (0.4, [190, 20, 0]),
+                (0.75, [255, 200, 0]),
+                (1.0, [255, 255, 255]),
+            ],
+            Ramp::Ocean => &[
+                (0.0, [5, 15, 60]),
+                (0.6, [20, 90, 170]),
+                (1.0, [150, 220, 240]),
+            ],
+        }
+    }
+    fn color(&self, value: f64) -> [u8; 3] {
+        let stops = self.stops();
+        let upper = stops
+            .iter()
+            .position(|(position, _)| *position >= value)
+            .unwrap_or(stops.len() - 1)
+            .max(1);
+        let (start, from) = stops[upper - 1];
+        let (end, to) = stops[upper];
+        let t = ((value - start) / (end - start)).clamp(0.0, 1.0);
+        [0, 1, 2].map(|i| (from[i] as f64 + (to[i] as f64 - from[i] as f64) * t).round() as u8)
+    }
+}
+
+// Permutation and lattice values drawn from the seeded generator
+struct Lattice {
+    permutation: [u8; 512],
+    values: [f64; 256],
+}
+
+impl Lattice {
+    fn new<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut shuffled = (0..=255).collect::<Vec<u8>>();
+        shuffled.shuffle(rng);
+        let mut permutation = [0; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = shuffled[i & 255];
+        }
+        let mut values = [0.0; 256];
+        rng.fill(&mut values[..]);
+        Self {
+            permutation,
+            values,
+        }
+    }
+    fn hash(&self, x: i64, y: i64) -> usize {
+        let x = self.permutation[(x & 255) as usize] as usize;
+        self.permutation[x + (y & 255) as usize] as usize
+    }
+    fn value(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (ix, iy) = (x0 as i64, y0 as i64);
+        let (u, v) = (fade(x - x0), fade(y - y0));
+        let corner = |dx, dy| self.values[self.hash(ix + dx, iy + dy)];
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        )
+    }
+    fn perlin(&self, x: f64, y: f64) -> f64 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (ix, iy) = (x0 as i64, y0 as i64);
+        let (fx, fy) = (x - x0, y - y0);
+        let (u, v) = (fade(fx), fade(fy));
+        let corner = |dx: i64, dy: i64| {
+            gradient(self.hash(ix + dx, iy + dy), fx - dx as f64, fy - dy as f64)
+        };
+        let value = lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        );
+        (value + 1.0) / 2.0
+    }
+    // Gustavson's 2D simplex noise
+    fn simplex(&self, x: f64, y: f64) -> f64 {
+        let skew = 0.5 * (3f64.sqrt() - 1.0);
+        let unskew = (3.0 - 3f64.sqrt()) / 6.0;
+
+        let s = (x + y) * skew;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * unskew;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let corners = [
+            (0, 0, x0, y0),
+            (i1, j1, x0 - i1 as f64 + unskew, y0 - j1 as f64 + unskew),
+            (1, 1, x0 - 1.0 + 2.0 * unskew, y0 - 1.0 + 2.0 * unskew),
+        ];
+
+        let (i, j) = (i as i64, j as i64);
+        let value = corners
+            .iter()
+            .map(|(di, dj, x, y)| {
+                let falloff = 0.5 - x * x - y * y;
+                if falloff < 0.0 {
+                    0.0
+                } else {
+                    falloff.powi(4) * gradient(self.hash(i + di, j + dj), *x, *y)
+                }
+            })
+            .sum::<f64>();
+        (70.0 * value + 1.0) / 2.0
+    }
+    // One feature point per cell, distance to the nearest one
+    fn worley(&self, x: f64, y: f64) -> f64 {
+        let (ix, iy) = (x.floor() as i64, y.floor() as i64);
+        let mut nearest = f64::MAX;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (cx, cy) = (ix + dx, iy + dy);
+                let hash = self.hash(cx, cy);
+                let px = cx as f64 + self.values[hash];
+                let py = cy as f64 + self.values[self.permutation[hash] as usize];
+                nearest = nearest.min((px - x).powi(2) + (py - y).powi(2));
+            }
+        }
+        nearest.sqrt()
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn gradient(hash: usize, x: f64, y: f64) -> f64 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub kind: Kind,
+    pub octaves: u32,
+    // Lattice cells across the image width for the first octave
+    pub scale: f64,
+    pub ramp: Ramp,
+}
+
+impl Image {
+    fn check(&self) -> Result<(), Error> {
+        if !(1..=MAX_SIZE).contains(&self.width) || !(1..=MAX_SIZE).contains(&self.height) {
+            return Err(Error::parameter(format!(
+                "width and height must be between 1 and {}",
+                MAX_SIZE
+            )));
+        }
+        if !(1..=MAX_OCTAVES).contains(&self.octaves) {
+            return Err(Error::parameter(format!(
+                "octaves must be between 1 and {}",
+                MAX_OCTAVES
+            )));
+        }
+        if !self.scale.is_finite() || self.scale <= 0.0 {
+            return Err(Error::parameter("scale must be a finite number above 0"));
+        }
+        Ok(())
+    }
+
+    // Values in [0, 1], row by row, octaves summed with halving amplitude
+    pub fn heights<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Vec<f64>, Error> {
+        self.check()?;
+        let size = self.width as usize * self.height as usize;
+        if self.kind == Kind::White {
+            return Ok((0..size).map(|_| rng.gen::<f64>()).collect());
+        }
+
+        let lattice = Lattice::new(rng);
+        let noise: fn(&Lattice, f64, f64) -> f64 = match self.kind {
+            Kind::Value => Lattice::value,
+            Kind::Perlin => Lattice::perlin,
+            Kind::Simplex => Lattice::simplex,
+            Kind::Worley | Kind::White => Lattice::worley,
+        };
+        let frequency = self.scale / self.width as f64;
+        let total = (0..self.octaves)
+            .map(|octave| PERSISTENCE.powi(octave as i32))
+            .sum::<f64>();
+
+        let mut heights = Vec::with_capacity(size);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (mut amplitude, mut scale, mut sum) = (1.0, frequency, 0.0);
+                for _ in 0..self.octaves {
+                    sum += amplitude * noise(&lattice, x as f64 * scale, y as f64 * scale);
+                    amplitude *= PERSISTENCE;
+                    scale *= LACUNARITY;
+                }
+                heights.push((sum / total).clamp(0.0, 1.0));
+            }
+        }
+        Ok(heights)
+    }
+
+    pub fn encode<W: Write>(&self, writer: W, heights: &[f64]) -> Result<(), Error> {
+        let mut encoder = Encoder::new(writer, self.width, self.height);
+        encoder.set_depth(BitDepth::Eight);
+        let data = if self.ramp == Ramp::Gray {
+            encoder.set_color(ColorType::Grayscale);
+            heights
+                .iter()
+                .map(|height| (height * 255.0).round() as u8)
+                .collect()
+        } else {
+            encoder.set_color(ColorType::Rgb);
+            heights
+                .iter()
+                .flat_map(|height| self.ramp.color(*height))
+                .collect::<Vec<_>>()
+        };
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .map_err(|error| match error {
+                png::EncodingError::IoError(error) => Error::Io(error),
+                error => Error::Io(io::Error::other(error)),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn image(kind: Kind) -> Image {
+        Image {
+            width: 32,
+            height: 16,
+            kind,
+            octaves: 3,
+            scale: 4.0,
+            ramp: Ramp::Gray,
+        }
+    }
+
+    #[test]
+    fn heights() {
+        for kind in [
+            Kind::White,
+            Kind::Value,
+            Kind::Perlin,
+            Kind::Simplex,
+            Kind::Worley,
+        ] {
+            let heights = image(kind)
+                .heights(&mut ChaCha20Rng::seed_from_u64(42))
+                .unwrap();
+            let again = image(kind)
+                .heights(&mut ChaCha20Rng::seed_from_u64(42))
+                .unwrap();
+
+            assert_eq!(heights.len(), 32 * 16);
+            assert_eq!(heights, again);
+            assert!(heights.iter().all(|height| (0.0..=1.0).contains(height)));
+            assert!(heights.iter().any(|height| *height != heights[0]));
+        }
+    }
+
+    #[test]
+    fn coherent() {
+        let heights = image(Kind::Perlin)
+            .heights(&mut ChaCha20Rng::seed_from_u64(42))
+            .unwrap();
+        let step = heights
+            .windows(2)
+            .map(|pair| (pair[0] - pair[1]).abs())
+            .fold(0.0, f64::max);
+
+        assert!(step < 0.2);
+    }
+
+    #[test]
+    fn ramps() {
+        assert_eq!(Ramp::Gray.color(0.0), [0, 0, 0]);
+        assert_eq!(Ramp::Gray.color(1.0), [255, 255, 255]);
+        assert_eq!(Ramp::Heat.color(0.2), [95, 10, 0]);
+        assert_eq!(Ramp::Ocean.color(2.0), [150, 220, 240]);
+    }
+
+    #[test]
+    fn encoded() {
+        let image = Image {
+            ramp: Ramp::Terrain,
+            ..image(Kind::Simplex)
+        };
+        let heights = image.heights(&mut ChaCha20Rng::seed_from_u64(42)).unwrap();
+        let mut png = Vec::new();
+        image.encode(&mut png, &heights).unwrap();
+
+        assert_eq!(&png[1..4], b"PNG");
+    }
+
+    #[test]
+    fn invalid() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+
+        for image in [
+            Image {
+                width: 0,
+                ..image(Kind::Value)
+            },
+            Image {
+                octaves: 0,
+                ..image(Kind::Value)
+            },
+            Image {
+                scale: f64::NAN,
+                ..image(Kind::Value)
+            },
+        ] {
+            assert!(image.heights(&mut rng).is_err());
+        }
+    }
+}
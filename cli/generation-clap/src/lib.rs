@@ -0,0 +1,22 @@
+pub mod bytes;
+pub mod charset;
+pub mod config;
+pub mod datetime;
+pub mod dice;
+pub mod distribution;
+pub mod error;
+pub mod generation;
+pub mod identifier;
+pub mod image;
+pub mod lines;
+pub mod maze;
+pub mod output;
+pub mod passphrase;
+pub mod password;
+pub mod pattern;
+pub mod quality;
+pub mod random;
+pub mod records;
+pub mod series;
+
+pub use generation::{Alphanumeric, Custom, Float, Generator, Grid, Range};
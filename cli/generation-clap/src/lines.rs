@@ -0,0 +1,257 @@
+use crate::distribution;
+use crate::error::Error;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_distr::WeightedIndex;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+// Weight taken from a 1-based column of each line
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    pub index: usize,
+    pub delimiter: String,
+}
+
+impl Column {
+    pub fn new(index: usize, delimiter: &str) -> Result<Self, Error> {
+        if index == 0 {
+            return Err(Error::parameter("weight column is 1-based"));
+        }
+        if delimiter.is_empty() {
+            return Err(Error::parameter("delimiter must not be empty"));
+        }
+        Ok(Self {
+            index,
+            delimiter: delimiter.into(),
+        })
+    }
+    pub fn weight(&self, number: u64, line: &str) -> Result<f64, Error> {
+        let field = line.split(self.delimiter.as_str()).nth(self.index - 1);
+        let weight = field
+            .and_then(|field| field.trim().parse::<f64>().ok())
+            .ok_or_else(|| {
+                Error::parameter(format!(
+                    "line {}: no numeric weight in column {}",
+                    number, self.index
+                ))
+            })?;
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(Error::parameter(format!(
+                "line {}: weight must be a finite number of at least 0",
+                number
+            )));
+        }
+        Ok(weight)
+    }
+}
+
+// Files are opened before anything is read, `-` or no files at all reads stdin
+pub fn lines(files: &[PathBuf]) -> Result<impl Iterator<Item = io::Result<String>>, Error> {
+    let readers = if files.is_empty() {
+        vec![Box::new(BufReader::new(io::stdin())) as Box<dyn BufRead>]
+    } else {
+        files
+            .iter()
+            .map(|file| -> Result<Box<dyn BufRead>, Error> {
+                if file == Path::new("-") {
+                    Ok(Box::new(BufReader::new(io::stdin())))
+                } else {
+                    Ok(Box::new(BufReader::new(File::open(file)?)))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(readers.into_iter().flat_map(BufRead::lines))
+}
+
+pub fn shuffle<R: Rng + ?Sized, I>(rng: &mut R, lines: I) -> Result<Vec<String>, Error>
+where
+    I: Iterator<Item = io::Result<String>>,
+{
+    let mut lines = lines.collect::<Result<Vec<_>, _>>()?;
+    lines.shuffle(rng);
+    Ok(lines)
+}
+
+struct Entry {
+    key: f64,
+    number: u64,
+    line: String,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.total_cmp(&other.key)
+    }
+}
+
+// Weighted reservoir (Efraimidis and Spirakis), keeps the `size` lines with the largest
+// ln(u) / weight keys in memory, unweighted lines all have weight 1
+pub fn sample<R: Rng + ?Sized, I>(
+    rng: &mut R,
+    lines: I,
+    size: usize,
+    column: Option<&Column>,
+) -> Result<Vec<String>, Error>
+where
+    I: Iterator<Item = io::Result<String>>,
+{
+    if size == 0 {
+        return Err(Error::parameter("sample size must be above 0"));
+    }
+    let mut reservoir = BinaryHeap::new();
+    for (number, line) in (1..).zip(lines) {
+        let line = line?;
+        let weight = match column {
+            Some(column) => column.weight(number, &line)?,
+            None => 1.0,
+        };
+        if weight == 0.0 {
+            continue;
+        }
+        let key = (1.0 - rng.gen::<f64>()).ln() / weight;
+        if reservoir.len() < size {
+            reservoir.push(Reverse(Entry { key, number, line }));
+        } else if reservoir
+            .peek()
+            .is_some_and(|Reverse(entry)| key > entry.key)
+        {
+            reservoir.pop();
+            reservoir.push(Reverse(Entry { key, number, line }));
+        }
+    }
+
+    // Selected lines keep their input order
+    let mut entries = reservoir
+        .into_iter()
+        .map(|Reverse(entry)| entry)
+        .collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|entry| entry.number);
+    Ok(entries.into_iter().map(|entry| entry.line).collect())
+}
+
+// Every pick is independent, so lines can repeat
+pub struct Pick {
+    pub lines: Vec<String>,
+    pub index: Option<WeightedIndex<f64>>,
+}
+
+impl Pick {
+    pub fn new<I>(lines: I, column: Option<&Column>) -> Result<Self, Error>
+    where
+        I: Iterator<Item = io::Result<String>>,
+    {
+        let lines = lines.collect::<Result<Vec<_>, _>>()?;
+        if lines.is_empty() {
+            return Err(Error::parameter("no input lines to pick from"));
+        }
+        let index = match column {
+            Some(column) => {
+                let weights = (1..)
+                    .zip(&lines)
+                    .map(|(number, line)| column.weight(number, line))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(distribution::index(&weights)?)
+            }
+            None => None,
+        };
+        Ok(Self { lines, index })
+    }
+    pub fn pick<R: Rng + ?Sized>(&self, rng: &mut R) -> &str {
+        let index = match &self.index {
+            Some(index) => rng.sample(index),
+            None => rng.gen_range(0..self.lines.len()),
+        };
+        &self.lines[index]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn input<'a>(lines: &'a [&str]) -> impl Iterator<Item = io::Result<String>> + 'a {
+        lines.iter().map(|line| Ok(line.to_string()))
+    }
+
+    #[test]
+    fn shuffled() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let mut lines = shuffle(&mut rng, input(&["a", "b", "c", "d"])).unwrap();
+
+        let mut again = ChaCha20Rng::seed_from_u64(42);
+        assert_eq!(
+            lines,
+            shuffle(&mut again, input(&["a", "b", "c", "d"])).unwrap()
+        );
+
+        lines.sort();
+        assert_eq!(lines, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn reservoir() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let numbers = (0..100).map(|i| i.to_string()).collect::<Vec<_>>();
+        let numbers = numbers.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let lines = sample(&mut rng, input(&numbers), 10, None).unwrap();
+        let mut order = lines
+            .iter()
+            .map(|line| line.parse::<u32>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(order.len(), 10);
+        order.dedup();
+        assert_eq!(order.len(), 10);
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(
+            sample(&mut rng, input(&["a", "b"]), 5, None).unwrap(),
+            vec!["a", "b"]
+        );
+        assert!(sample(&mut rng, input(&["a"]), 0, None).is_err());
+    }
+
+    #[test]
+    fn weighted() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let column = Column::new(2, ",").unwrap();
+        let lines = ["a,0", "b,1", "c,0"];
+
+        for _ in 0..16 {
+            assert_eq!(
+                sample(&mut rng, input(&lines), 2, Some(&column)).unwrap(),
+                vec!["b,1"]
+            );
+
+            let pick = Pick::new(input(&lines), Some(&column)).unwrap();
+            assert_eq!(pick.pick(&mut rng), "b,1");
+        }
+
+        assert!(sample(&mut rng, input(&["a"]), 1, Some(&column)).is_err());
+        assert!(Pick::new(input(&["a,-1"]), Some(&column)).is_err());
+        assert!(Pick::new(input(&["a,1e308", "b,1e308"]), Some(&column)).is_err());
+        assert!(Pick::new(input(&[]), None).is_err());
+        assert!(Column::new(0, ",").is_err());
+    }
+}
@@ -1,45 +1,719 @@
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use rand::Rng;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::process::ExitCode;
+use std::time::Instant;
 
 mod cli;
-mod generation;
+mod serve;
 
+use chrono::Utc;
 use cli::*;
-use generation::*;
+use generation_clap::bytes::{self, Encoding};
+use generation_clap::config::{Seed, Settings};
+use generation_clap::datetime::{self, Layout};
+use generation_clap::dice::{self, Expression};
+use generation_clap::error::Error;
+use generation_clap::generation::{round, signed, unsigned, CHARACTERS, LENGTH};
+use generation_clap::image::Image;
+use generation_clap::lines::{self, Column, Pick};
+use generation_clap::maze::{Maze, Render};
+use generation_clap::output::{Format, Output, Value};
+use generation_clap::passphrase::{self, Passphrase};
+use generation_clap::password::{Password, Policy};
+use generation_clap::pattern::Pattern;
+use generation_clap::random::Random;
+use generation_clap::records::{Records, Schema};
+use generation_clap::series::{Model, Series};
+use generation_clap::{charset, distribution, identifier, quality};
+use generation_clap::{Alphanumeric, Custom, Float, Generator, Range};
 
 // cargo run -- --help
+// cargo run -- --seed 42 --rng pcg string
+// cargo run -- --count 1000000 --format csv --field amount float-range -s 0 -e 100
+// cargo run -- --count 6 integer-range -s 1 -e 49 --inclusive --unique
+// cargo run -- custom --length 12 --preset alnum,symbols --exclude 0O1lI
+// cargo run -- --count 10 pattern '[A-Z]{3}-\d{4}'
+// cargo run -- --count 10 weighted red:5 green:3 blue:1
+// cargo run -- --count 100 --format ndjson records --schema schema.toml
+// cargo run -- --count 5 roll 4d6kh3 + 1d4!
+// cargo run -- roll --stats 2d20kh1
+// cargo run -- --count 5 datetime --after -30d --timezone Europe/Berlin --pattern unix
+// cargo run -- --count 1440 --format csv series --amplitude 20 --walk 0.5 --spikes 0.01
+// cargo run -- bytes --size 1G --encoding raw --output blob.bin
+// cargo run -- --seed 3 image --kind simplex --octaves 6 --ramp terrain --output map.png
+// cargo run -- --seed 5 maze --algorithm wilson --braid 0.3 --render png --output maze.png
+// cargo run -- --seed 1 sample -k 100 cases.txt
+// cargo run -- serve --port 8080 && curl 'localhost:8080/range?start=1&end=10&count=5&format=json'
+// cargo run -- --seed 7 test --samples 100000 --histogram
+// GENERATION_CLAP_SEED=report cargo run -- --profile api-keys --count 3
+// cargo run -- config show
 
-fn main() {
-    let cli = CLI::parse();
+type Sampler<'a> = Box<dyn FnMut(&mut Random) -> Vec<Value> + 'a>;
 
-    match &cli.command {
-        Some(Commands::Float {}) => {
-            println!("{}", float());
-        }
+// Field names and a sampler producing one record per call
+type Source<'a> = (Vec<String>, Sampler<'a>);
+
+fn single<'a>(mut sample: impl FnMut(&mut Random) -> Value + 'a) -> Sampler<'a> {
+    Box::new(move |rng| vec![sample(rng)])
+}
+
+fn generator<'a, G>(generator: G) -> Sampler<'a>
+where
+    G: Generator + 'a,
+    G::Output: Into<Value>,
+{
+    single(move |rng| generator.generate(rng).into())
+}
+
+fn sampler<'a>(
+    command: &'a Option<Commands>,
+    field: &str,
+    count: u64,
+) -> Result<Source<'a>, Error> {
+    let sampler: Sampler = match command {
+        Some(Commands::Float {}) => generator(Float::new()),
         Some(Commands::Integer { sign }) => {
             if *sign {
-                println!("{}", signed());
+                single(|rng| signed(rng).into())
+            } else {
+                single(|rng| unsigned(rng).into())
+            }
+        }
+        Some(Commands::String { length }) => generator(match length {
+            Some(length) => Alphanumeric::new(*length),
+            None => Alphanumeric::default(),
+        }),
+        Some(Commands::FloatRange {
+            start,
+            end,
+            inclusive,
+            step,
+            decimals,
+        }) => {
+            let range = if *inclusive {
+                Range::inclusive(*start, *end)?
             } else {
-                println!("{}", unsigned());
+                Range::new(*start, *end)?
+            };
+            let decimals = *decimals;
+            let round = move |value: f64| decimals.map_or(value, |decimals| round(value, decimals));
+            match step {
+                Some(step) => {
+                    let grid = range.step(*step)?;
+                    single(move |rng| round(grid.generate(rng)).into())
+                }
+                None => single(move |rng| round(range.generate(rng)).into()),
             }
         }
-        Some(Commands::String { length }) => {
-            if let Some(length) = length {
-                println!("{}", string(*length));
+        Some(Commands::IntegerRange {
+            start,
+            end,
+            inclusive,
+            step,
+            unique,
+        }) => {
+            let range = if *inclusive {
+                Range::inclusive(*start, *end)?
             } else {
-                println!("{}", string(LENGTH));
+                Range::new(*start, *end)?
+            };
+            match (step, unique) {
+                (step, true) => {
+                    let grid = range.step(step.unwrap_or(1))?;
+                    if count > grid.points() {
+                        return Err(Error::parameter(format!(
+                            "cannot draw {} unique values from a range of {}",
+                            count,
+                            grid.points()
+                        )));
+                    }
+                    // Drawn at once on the first record so they can be distinct
+                    let mut values = None;
+                    single(move |rng| {
+                        values
+                            .get_or_insert_with(|| grid.distinct(rng, count).into_iter())
+                            .next()
+                            .expect("count is checked")
+                            .into()
+                    })
+                }
+                (Some(step), false) => generator(range.step(*step)?),
+                (None, false) => generator(range),
             }
         }
-        Some(Commands::FloatRange { start, end }) => {
-            println!("{}", range(*start, *end));
+        Some(Commands::Custom {
+            length,
+            chars,
+            preset,
+            exclude,
+        }) => {
+            let characters = charset::charset(chars.as_deref(), preset, exclude.as_deref())?;
+            generator(Custom::new(length.unwrap_or(LENGTH), characters)?)
+        }
+        Some(Commands::Normal { mean, stddev }) => {
+            let normal = distribution::normal(*mean, *stddev)?;
+            single(move |rng| rng.sample(normal).into())
+        }
+        Some(Commands::Exponential { lambda }) => {
+            let exponential = distribution::exponential(*lambda)?;
+            single(move |rng| rng.sample(exponential).into())
         }
-        Some(Commands::IntegerRange { start, end }) => {
-            println!("{}", range(*start, *end));
+        Some(Commands::Poisson { lambda }) => {
+            let poisson = distribution::poisson(*lambda)?;
+            single(move |rng| (rng.sample(poisson) as u64).into())
         }
-        Some(Commands::Custom { length, chars }) => {
-            println!("{}", custom(*length, chars.as_bytes()));
+        Some(Commands::Binomial {
+            trials,
+            probability,
+        }) => {
+            let binomial = distribution::binomial(*trials, *probability)?;
+            single(move |rng| rng.sample(binomial).into())
         }
-        None => {
-            println!("{}", custom(LENGTH, CHARACTERS));
+        Some(Commands::Lognormal { mean, stddev }) => {
+            let lognormal = distribution::lognormal(*mean, *stddev)?;
+            single(move |rng| rng.sample(lognormal).into())
+        }
+        Some(Commands::Bernoulli { probability }) => {
+            let bernoulli = distribution::bernoulli(*probability)?;
+            single(move |rng| rng.sample(bernoulli).into())
+        }
+        Some(Commands::Weighted { choices, file }) => {
+            let weighted = distribution::weighted(choices, file.as_deref())?;
+            single(move |rng| weighted.values[rng.sample(&weighted.index)].clone().into())
+        }
+        Some(Commands::Uuid {
+            version,
+            case,
+            simple,
+        }) => {
+            let hyphens = !simple;
+            single(move |rng| identifier::uuid(rng, *version, *case, hyphens).into())
+        }
+        Some(Commands::Ulid { case }) => single(|rng| identifier::ulid(rng, *case).into()),
+        Some(Commands::Nanoid { size, alphabet }) => {
+            let alphabet = identifier::alphabet(alphabet)?;
+            single(move |rng| identifier::nanoid(rng, *size, &alphabet).into())
+        }
+        Some(Commands::Password {
+            length,
+            min_lower,
+            min_upper,
+            min_digits,
+            min_symbols,
+            no_ambiguous,
+            no_repeats,
+            min_entropy,
+        }) => {
+            let password = Password::new(&Policy {
+                length: *length,
+                lower: *min_lower,
+                upper: *min_upper,
+                digits: *min_digits,
+                symbols: *min_symbols,
+                ambiguous: !no_ambiguous,
+                repeats: !no_repeats,
+                entropy: *min_entropy,
+            })?;
+            eprintln!("entropy: {:.1} bits", password.entropy());
+            single(move |_| password.generate().into())
+        }
+        Some(Commands::Passphrase {
+            words,
+            file,
+            separator,
+            capitalize,
+            digits,
+        }) => {
+            let passphrase = Passphrase::new(
+                passphrase::words(file.as_deref())?,
+                *words,
+                separator,
+                *capitalize,
+                *digits,
+            )?;
+            eprintln!(
+                "entropy: {:.1} bits ({} words, {:.1} bits per word)",
+                passphrase.entropy(),
+                words,
+                passphrase.bits_per_word()
+            );
+            single(move |rng| passphrase.generate(rng).into())
+        }
+        Some(Commands::Records { schema }) => {
+            let mut records = Records::new(&Schema::load(schema)?, count)?;
+            return Ok((records.fields(), Box::new(move |rng| records.generate(rng))));
+        }
+        Some(Commands::Roll { expression, .. }) => {
+            let expression = Expression::new(&expression.join(" "))?;
+            return Ok((
+                vec![field.to_string(), "rolls".to_string()],
+                Box::new(move |rng| {
+                    let roll = expression.roll(rng);
+                    vec![roll.total.into(), roll.detail.into()]
+                }),
+            ));
+        }
+        Some(Commands::Series {
+            start,
+            interval,
+            level,
+            trend,
+            amplitude,
+            period,
+            walk,
+            noise,
+            spikes,
+            spike,
+            decimals,
+            pattern,
+        }) => {
+            let start = datetime::instant(start, Utc::now())?;
+            let interval = datetime::duration(interval)?;
+            let period = datetime::duration(period)?;
+            let model = Model {
+                level: *level,
+                trend: *trend,
+                amplitude: *amplitude,
+                period: period.num_milliseconds() as f64
+                    / interval.num_milliseconds().max(1) as f64,
+                walk: *walk,
+                noise: *noise,
+                spikes: *spikes,
+                spike: *spike,
+            };
+            let mut series = Series::new(model, start, interval, count)?;
+            let layout = Layout::new(pattern)?;
+            let decimals = *decimals;
+            return Ok((
+                vec!["timestamp".to_string(), field.to_string()],
+                Box::new(move |rng| {
+                    let (time, value) = series.next(rng);
+                    let value = decimals.map_or(value, |decimals| round(value, decimals));
+                    vec![layout.value(time), value.into()]
+                }),
+            ));
+        }
+        Some(Commands::Pattern {
+            pattern,
+            max_repeat,
+        }) => {
+            let pattern = Pattern::new(pattern, *max_repeat)?;
+            single(move |rng| pattern.generate(rng).into())
+        }
+        Some(Commands::Datetime {
+            after,
+            before,
+            timezone,
+            pattern,
+        }) => {
+            let now = Utc::now();
+            let (after, before) = (
+                datetime::instant(after, now)?,
+                datetime::instant(before, now)?,
+            );
+            // Values are whole milliseconds, so the bounds must differ in them
+            if after.timestamp_millis() >= before.timestamp_millis() {
+                return Err(Error::parameter(
+                    "after must be at least a millisecond earlier than before",
+                ));
+            }
+            let timezone = datetime::timezone(timezone)?;
+            let layout = Layout::new(pattern)?;
+            single(move |rng| {
+                layout.value(datetime::between(rng, after, before).with_timezone(&timezone))
+            })
+        }
+        Some(Commands::Duration { min, max, style }) => {
+            let (min, max) = (datetime::duration(min)?, datetime::duration(max)?);
+            if min > max {
+                return Err(Error::parameter("min must not be above max"));
+            }
+            single(move |rng| style.value(datetime::interval(rng, min, max)))
+        }
+        Some(Commands::Pick {
+            files,
+            weights,
+            delimiter,
+        }) => {
+            let column = column(*weights, delimiter)?;
+            let pick = Pick::new(lines::lines(files)?, column.as_ref())?;
+            single(move |rng| pick.pick(rng).to_string().into())
+        }
+        Some(Commands::Bytes { size, encoding, .. }) => {
+            let size = usize::try_from(bytes::size(size)?)
+                .map_err(|_| Error::parameter("size does not fit in memory"))?;
+            single(move |rng| {
+                let bytes = bytes::bytes(rng, size);
+                encoding
+                    .encode(&bytes)
+                    .expect("raw bytes are written by run")
+                    .into()
+            })
+        }
+        Some(
+            Commands::Image { .. }
+            | Commands::Shuffle { .. }
+            | Commands::Maze { .. }
+            | Commands::Sample { .. }
+            | Commands::Serve { .. }
+            | Commands::Config { .. }
+            | Commands::Test { .. },
+        ) => {
+            unreachable!("handled by run")
+        }
+        None => generator(Custom::default()),
+    };
+
+    Ok((vec![field.into()], sampler))
+}
+
+fn column(weights: Option<usize>, delimiter: &str) -> Result<Option<Column>, Error> {
+    weights
+        .map(|index| Column::new(index, delimiter))
+        .transpose()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn test(
+    rng: &mut Random,
+    samples: u64,
+    start: i64,
+    end: i64,
+    buckets: usize,
+    length: usize,
+    chars: &str,
+    alpha: f64,
+    histogram: bool,
+) -> Result<(), Error> {
+    if samples == 0 || length == 0 {
+        return Err(Error::parameter("samples and length must be above 0"));
+    }
+    if buckets < 2 {
+        return Err(Error::parameter("at least 2 buckets are needed"));
+    }
+    if !(0.0..1.0).contains(&alpha) {
+        return Err(Error::parameter("alpha must be between 0 and 1"));
+    }
+
+    let characters = charset::parse(chars)?;
+    let checks = [
+        quality::uniformity(rng, samples, start, end, buckets)?,
+        quality::histogram(rng, samples, buckets),
+        quality::frequency(rng, samples, length, &characters)?,
+        quality::serial(rng, samples, length, &characters)?,
+    ];
+    let mut writer = BufWriter::new(io::stdout().lock());
+    quality::report(&mut writer, &checks, alpha, histogram)?;
+    writer.flush()?;
+
+    let failed = checks.iter().filter(|check| !check.passed(alpha)).count();
+    if failed > 0 {
+        return Err(Error::Check(format!(
+            "{} of {} checks failed at alpha {}",
+            failed,
+            checks.len(),
+            alpha
+        )));
+    }
+    Ok(())
+}
+
+fn write(cli: &Cli, lines: Vec<String>) -> Result<(), Error> {
+    let mut output = Output::new(
+        BufWriter::new(io::stdout().lock()),
+        cli.format,
+        vec![cli.field.clone()],
+    )?;
+    for line in lines {
+        output.write(&[line.into()])?;
+    }
+    output.finish()?;
+    Ok(())
+}
+
+fn run(cli: &Cli, settings: &Settings) -> Result<(), Error> {
+    let mut rng = Random::new(cli.rng, cli.seed);
+
+    match &cli.command {
+        Some(Commands::Serve {
+            port,
+            host,
+            max_count,
+        }) => {
+            return serve::serve(host, *port, *max_count, settings);
+        }
+        Some(Commands::Config {
+            command: ConfigCommands::Show,
+        }) => {
+            settings.show(&mut io::stdout().lock())?;
+            return Ok(());
+        }
+        Some(Commands::Roll {
+            expression,
+            stats: true,
+        }) => {
+            let distribution = Expression::new(&expression.join(" "))?.distribution()?;
+            dice::report(&mut io::stdout().lock(), &distribution)?;
+            return Ok(());
+        }
+        Some(Commands::Test {
+            samples,
+            start,
+            end,
+            buckets,
+            length,
+            chars,
+            alpha,
+            histogram,
+        }) => {
+            return test(
+                &mut rng,
+                *samples,
+                *start,
+                *end,
+                *buckets,
+                length.unwrap_or(LENGTH),
+                chars.as_deref().unwrap_or(CHARACTERS),
+                *alpha,
+                *histogram,
+            );
+        }
+        Some(Commands::Image {
+            width,
+            height,
+            kind,
+            octaves,
+            scale,
+            ramp,
+            output,
+        }) => {
+            let image = Image {
+                width: *width,
+                height: *height,
+                kind: *kind,
+                octaves: *octaves,
+                scale: *scale,
+                ramp: *ramp,
+            };
+            let heights = image.heights(&mut rng)?;
+            image.encode(BufWriter::new(File::create(output)?), &heights)?;
+            return Ok(());
+        }
+        Some(Commands::Maze {
+            width,
+            height,
+            algorithm,
+            braid,
+            render,
+            cell,
+            wall,
+            output,
+        }) => {
+            if *render == Render::Png && output.is_none() {
+                return Err(Error::parameter("png needs --output"));
+            }
+            let mut maze = Maze::generate(&mut rng, *width, *height, *algorithm)?;
+            maze.braid(&mut rng, *braid)?;
+            let mut writer: Box<dyn Write> = match output {
+                Some(output) => Box::new(BufWriter::new(File::create(output)?)),
+                None => Box::new(BufWriter::new(io::stdout().lock())),
+            };
+            match render {
+                Render::Ascii => write!(writer, "{}", maze.ascii())?,
+                Render::Rects => writeln!(
+                    writer,
+                    "{}",
+                    serde_json::to_string(&maze.rectangles(*cell, *wall)?)
+                        .expect("rectangles serialize")
+                )?,
+                Render::Png => maze.encode(&mut writer, *cell, *wall)?,
+            }
+            writer.flush()?;
+            return Ok(());
+        }
+        Some(Commands::Shuffle { files }) => {
+            return write(cli, lines::shuffle(&mut rng, lines::lines(files)?)?);
+        }
+        Some(Commands::Sample {
+            size,
+            files,
+            weights,
+            delimiter,
+        }) => {
+            let column = column(*weights, delimiter)?;
+            let lines = lines::sample(&mut rng, lines::lines(files)?, *size, column.as_ref())?;
+            return write(cli, lines);
+        }
+        Some(Commands::Bytes {
+            size,
+            encoding,
+            output,
+        }) => {
+            let total = bytes::size(size)?
+                .checked_mul(cli.count)
+                .ok_or_else(|| Error::parameter("size times count is too large"))?;
+            if let Some(output) = output {
+                let start = Instant::now();
+                let written =
+                    bytes::stream(&mut rng, &mut File::create(output)?, total, *encoding)?;
+                eprintln!("{}", bytes::throughput(total, written, start.elapsed()));
+                return Ok(());
+            }
+            if *encoding == Encoding::Raw {
+                if cli.format != Format::Plain {
+                    return Err(Error::parameter("raw bytes need plain format or --output"));
+                }
+                let mut stdout = io::stdout().lock();
+                bytes::stream(&mut rng, &mut stdout, total, *encoding)?;
+                stdout.flush()?;
+                return Ok(());
+            }
+            if cli.format == Format::Plain {
+                let size = bytes::size(size)?;
+                let mut writer = BufWriter::new(io::stdout().lock());
+                for _ in 0..cli.count {
+                    bytes::stream(&mut rng, &mut writer, size, *encoding)?;
+                    writer.write_all(b"\n")?;
+                }
+                writer.flush()?;
+                return Ok(());
+            }
+        }
+        _ => {}
+    }
+
+    generate(cli, BufWriter::new(io::stdout().lock()))?;
+
+    Ok(())
+}
+
+// Writes `count` records of the sampled command, shared by the command line and the server
+fn generate<W: Write>(cli: &Cli, writer: W) -> Result<W, Error> {
+    let mut rng = Random::new(cli.rng, cli.seed);
+    let (fields, mut sample) = sampler(&cli.command, &cli.field, cli.count)?;
+    let mut output = Output::new(writer, cli.format, fields)?;
+
+    for _ in 0..cli.count {
+        output.write(&sample(&mut rng))?;
+    }
+
+    Ok(output.finish()?)
+}
+
+fn parse<T: AsRef<OsStr>>(arguments: &[T]) -> Result<(Cli, ArgMatches), clap::Error> {
+    let matches = Cli::command().try_get_matches_from(arguments.iter().map(AsRef::as_ref))?;
+    Ok((Cli::from_arg_matches(&matches)?, matches))
+}
+
+// Fills options left off the command line from the config file and environment
+fn configure(mut cli: Cli, matches: &ArgMatches, settings: &Settings) -> Cli {
+    if matches.value_source("format") != Some(ValueSource::CommandLine) {
+        cli.format = settings.format.value;
+    }
+    if cli.seed.is_none() && !matches!(cli.command, Some(Commands::Config { .. })) {
+        cli.seed = match settings.seed.value {
+            Seed::Random => None,
+            Seed::Report => {
+                let seed = rand::random();
+                eprintln!("seed: {}", seed);
+                Some(seed)
+            }
+            Seed::Fixed(seed) => Some(seed),
+        };
+    }
+
+    let length = settings.length.value;
+    let charset = || settings.charset.value.clone();
+    let command = cli.command.get_or_insert_with(|| Commands::Custom {
+        length: None,
+        chars: None,
+        preset: Vec::new(),
+        exclude: None,
+    });
+    match command {
+        Commands::String { length: value } => {
+            value.get_or_insert(length);
+        }
+        Commands::Custom {
+            length: value,
+            chars,
+            preset,
+            ..
+        } => {
+            value.get_or_insert(length);
+            if preset.is_empty() {
+                chars.get_or_insert_with(charset);
+            }
+        }
+        Commands::Test {
+            length: value,
+            chars,
+            ..
+        } => {
+            value.get_or_insert(length);
+            chars.get_or_insert_with(charset);
+        }
+        _ => {}
+    }
+    cli
+}
+
+// Parses the command line, a profile adds its subcommand and the options not given already
+fn load(settings: &Settings) -> Result<Cli, Error> {
+    let mut arguments = env::args_os().collect::<Vec<_>>();
+    let (mut cli, mut matches) = parse(&arguments).unwrap_or_else(|error| error.exit());
+
+    if let Some(name) = &cli.profile {
+        if cli.command.is_some() {
+            return Err(Error::parameter(
+                "--profile cannot be used with a subcommand",
+            ));
+        }
+        let (command, options) = settings.profile(name)?;
+        let globals = Cli::command();
+        let given = |key: &str| {
+            let key = key.replace('_', "-");
+            globals.get_arguments().any(|arg| {
+                (arg.get_long() == Some(key.as_str()) || arg.get_id() == key.as_str())
+                    && matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            })
+        };
+        let options = options.into_iter().filter(|(key, _)| !given(key));
+        let options = cli::arguments(&command, options)?;
+        arguments.extend(options.into_iter().map(OsString::from));
+        (cli, matches) = parse(&arguments).unwrap_or_else(|error| error.exit());
+    }
+    // These print their input lines once, sample takes its size from -k
+    if matches!(
+        cli.command,
+        Some(Commands::Shuffle { .. } | Commands::Sample { .. })
+    ) && matches.value_source("count") == Some(ValueSource::CommandLine)
+    {
+        return Err(Error::parameter(
+            "--count does not apply to shuffle and sample, use -k for the sample size",
+        ));
+    }
+
+    Ok(configure(cli, &matches, settings))
+}
+
+fn main() -> ExitCode {
+    let result = Settings::load().and_then(|settings| {
+        let cli = load(&settings)?;
+        run(&cli, &settings)
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Error::Io(error)) if error.kind() == ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(error.code())
         }
     }
 }
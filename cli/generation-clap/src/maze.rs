@@ -0,0 +1,436 @@
+use crate::error::Error;
+use crate::image::MAX_SIZE;
+use clap::ValueEnum;
+use png::{BitDepth, ColorType, Encoder};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::io::{self, Write};
+
+pub const MAX_CELLS: usize = 1_024;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Algorithm {
+    /// Depth first search, long winding corridors
+    Backtracker,
+    /// Randomized Prim, many short dead ends
+    Prim,
+    /// Randomized Kruskal, joins random walls between regions
+    Kruskal,
+    /// Loop erased random walks, uniform over all perfect mazes
+    Wilson,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Render {
+    /// Text with walls drawn as `#`
+    Ascii,
+    /// Black walls on white, needs --output
+    Png,
+    /// JSON list of [x, y, w, h] wall rectangles
+    Rects,
+}
+
+// Cells in row major order, passages are stored on the east and south side of a cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct Maze {
+    width: usize,
+    height: usize,
+    east: Vec<bool>,
+    south: Vec<bool>,
+}
+
+impl Maze {
+    // Every wall closed
+    pub fn new(width: usize, height: usize) -> Result<Self, Error> {
+        if !(1..=MAX_CELLS).contains(&width) || !(1..=MAX_CELLS).contains(&height) {
+            return Err(Error::parameter(format!(
+                "width and height must be between 1 and {}",
+                MAX_CELLS
+            )));
+        }
+        Ok(Self {
+            width,
+            height,
+            east: vec![false; width * height],
+            south: vec![false; width * height],
+        })
+    }
+
+    pub fn generate<R: Rng + ?Sized>(
+        rng: &mut R,
+        width: usize,
+        height: usize,
+        algorithm: Algorithm,
+    ) -> Result<Self, Error> {
+        let mut maze = Self::new(width, height)?;
+        match algorithm {
+            Algorithm::Backtracker => maze.backtracker(rng),
+            Algorithm::Prim => maze.prim(rng),
+            Algorithm::Kruskal => maze.kruskal(rng),
+            Algorithm::Wilson => maze.wilson(rng),
+        }
+        Ok(maze)
+    }
+
+    fn neighbours(&self, cell: usize) -> Vec<usize> {
+        let (x, y) = (cell % self.width, cell / self.width);
+        let mut neighbours = Vec::with_capacity(4);
+        if x > 0 {
+            neighbours.push(cell - 1);
+        }
+        if x + 1 < self.width {
+            neighbours.push(cell + 1);
+        }
+        if y > 0 {
+            neighbours.push(cell - self.width);
+        }
+        if y + 1 < self.height {
+            neighbours.push(cell + self.width);
+        }
+        neighbours
+    }
+
+    // Upper or left cell of two adjacent ones, and whether the passage runs south
+    fn between(&self, a: usize, b: usize) -> (usize, bool) {
+        let (a, b) = (a.min(b), a.max(b));
+        (a, b == a + self.width)
+    }
+
+    fn open(&mut self, a: usize, b: usize) {
+        match self.between(a, b) {
+            (cell, true) => self.south[cell] = true,
+            (cell, false) => self.east[cell] = true,
+        }
+    }
+
+    fn is_open(&self, a: usize, b: usize) -> bool {
+        match self.between(a, b) {
+            (cell, true) => self.south[cell],
+            (cell, false) => self.east[cell],
+        }
+    }
+
+    fn exits(&self, cell: usize) -> usize {
+        self.neighbours(cell)
+            .into_iter()
+            .filter(|neighbour| self.is_open(cell, *neighbour))
+            .count()
+    }
+
+    fn backtracker<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut visited = vec![false; self.width * self.height];
+        let start = rng.gen_range(0..visited.len());
+        visited[start] = true;
+        let mut stack = vec![start];
+        while let Some(&cell) = stack.last() {
+            let unvisited = self
+                .neighbours(cell)
+                .into_iter()
+                .filter(|neighbour| !visited[*neighbour])
+                .collect::<Vec<_>>();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    self.open(cell, next);
+                    visited[next] = true;
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    fn prim<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut inside = vec![false; self.width * self.height];
+        let start = rng.gen_range(0..inside.len());
+        inside[start] = true;
+        let mut frontier = self
+            .neighbours(start)
+            .into_iter()
+            .map(|neighbour| (start, neighbour))
+            .collect::<Vec<_>>();
+        while !frontier.is_empty() {
+            let (cell, next) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            if inside[next] {
+                continue;
+            }
+            self.open(cell, next);
+            inside[next] = true;
+            for neighbour in self.neighbours(next) {
+                if !inside[neighbour] {
+                    frontier.push((next, neighbour));
+                }
+            }
+        }
+    }
+
+    fn kruskal<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let cells = self.width * self.height;
+        let mut walls = (0..cells)
+            .flat_map(|cell| {
+                let east = (cell % self.width + 1 < self.width).then_some((cell, cell + 1));
+                let south = (cell + self.width < cells).then_some((cell, cell + self.width));
+                east.into_iter().chain(south)
+            })
+            .collect::<Vec<_>>();
+        walls.shuffle(rng);
+
+        // Union find with path halving
+        let mut parent = (0..cells).collect::<Vec<_>>();
+        let find = |parent: &mut Vec<usize>, mut cell: usize| {
+            while parent[cell] != cell {
+                parent[cell] = parent[parent[cell]];
+                cell = parent[cell];
+            }
+            cell
+        };
+        for (a, b) in walls {
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            if root_a != root_b {
+                parent[root_a] = root_b;
+                self.open(a, b);
+            }
+        }
+    }
+
+    fn wilson<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let cells = self.width * self.height;
+        let mut inside = vec![false; cells];
+        inside[rng.gen_range(0..cells)] = true;
+        // Latest exit of every cell on the walk, revisits overwrite it and so erase loops
+        let mut next = vec![0; cells];
+        let mut order = (0..cells).collect::<Vec<_>>();
+        order.shuffle(rng);
+        for start in order {
+            let mut cell = start;
+            while !inside[cell] {
+                let neighbours = self.neighbours(cell);
+                next[cell] = *neighbours.choose(rng).expect("cells have neighbours");
+                cell = next[cell];
+            }
+            let mut cell = start;
+            while !inside[cell] {
+                inside[cell] = true;
+                self.open(cell, next[cell]);
+                cell = next[cell];
+            }
+        }
+    }
+
+    // Opens each dead end with the given probability, preferring walls to other dead ends
+    pub fn braid<R: Rng + ?Sized>(&mut self, rng: &mut R, probability: f64) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(Error::parameter("braid must be between 0 and 1"));
+        }
+        let mut cells = (0..self.width * self.height).collect::<Vec<_>>();
+        cells.shuffle(rng);
+        for cell in cells {
+            if self.exits(cell) != 1 || !rng.gen_bool(probability) {
+                continue;
+            }
+            let closed = self
+                .neighbours(cell)
+                .into_iter()
+                .filter(|neighbour| !self.is_open(cell, *neighbour))
+                .collect::<Vec<_>>();
+            let dead = closed
+                .iter()
+                .copied()
+                .filter(|neighbour| self.exits(*neighbour) == 1)
+                .collect::<Vec<_>>();
+            let candidates = if dead.is_empty() { closed } else { dead };
+            if let Some(&neighbour) = candidates.choose(rng) {
+                self.open(cell, neighbour);
+            }
+        }
+        Ok(())
+    }
+
+    // Grid of 2 * width + 1 by 2 * height + 1 blocks, true for walls
+    pub fn blocks(&self) -> Vec<Vec<bool>> {
+        let mut blocks = vec![vec![true; 2 * self.width + 1]; 2 * self.height + 1];
+        for cell in 0..self.width * self.height {
+            let (x, y) = (2 * (cell % self.width) + 1, 2 * (cell / self.width) + 1);
+            blocks[y][x] = false;
+            if self.east[cell] {
+                blocks[y][x + 1] = false;
+            }
+            if self.south[cell] {
+                blocks[y + 1][x] = false;
+            }
+        }
+        blocks
+    }
+
+    pub fn ascii(&self) -> String {
+        self.blocks()
+            .iter()
+            .map(|row| {
+                let line = row
+                    .iter()
+                    .map(|wall| if *wall { "##" } else { "  " })
+                    .collect::<String>();
+                format!("{}\n", line)
+            })
+            .collect()
+    }
+
+    // Width and height in pixels, which keeps every offset below `MAX_SIZE`
+    fn size(&self, cell: u32, wall: u32) -> Result<(u32, u32), Error> {
+        let size = |cells: usize| (cells as u64 + 1) * wall as u64 + cells as u64 * cell as u64;
+        let (width, height) = (size(self.width), size(self.height));
+        if cell == 0 || wall == 0 || width > MAX_SIZE as u64 || height > MAX_SIZE as u64 {
+            return Err(Error::parameter(format!(
+                "cell and wall must be above 0 and the maze at most {} pixels wide and high",
+                MAX_SIZE
+            )));
+        }
+        Ok((width as u32, height as u32))
+    }
+
+    // Walls as [x, y, w, h] in pixels, neighbouring blocks merged greedily into rectangles
+    pub fn rectangles(&self, cell: u32, wall: u32) -> Result<Vec<[i32; 4]>, Error> {
+        self.size(cell, wall)?;
+        let blocks = self.blocks();
+        let (rows, columns) = (blocks.len(), blocks[0].len());
+        // Walls sit on even blocks, cells on odd ones
+        let offset = |block: usize| block.div_ceil(2) as u32 * wall + (block / 2) as u32 * cell;
+
+        let mut used = vec![vec![false; columns]; rows];
+        let mut rectangles = Vec::new();
+        for y in 0..rows {
+            for x in 0..columns {
+                if !blocks[y][x] || used[y][x] {
+                    continue;
+                }
+                let free = |used: &Vec<Vec<bool>>, y: usize, x: usize| blocks[y][x] && !used[y][x];
+                let mut right = x + 1;
+                while right < columns && free(&used, y, right) {
+                    right += 1;
+                }
+                let mut bottom = y + 1;
+                while bottom < rows && (x..right).all(|x| free(&used, bottom, x)) {
+                    bottom += 1;
+                }
+                for row in &mut used[y..bottom] {
+                    row[x..right].fill(true);
+                }
+                let (left, top) = (offset(x), offset(y));
+                rectangles.push([
+                    left as i32,
+                    top as i32,
+                    (offset(right) - left) as i32,
+                    (offset(bottom) - top) as i32,
+                ]);
+            }
+        }
+        Ok(rectangles)
+    }
+
+    pub fn encode<W: Write>(&self, writer: W, cell: u32, wall: u32) -> Result<(), Error> {
+        let (width, height) = self.size(cell, wall)?;
+        let mut data = vec![255; width as usize * height as usize];
+        for [x, y, w, h] in self.rectangles(cell, wall)? {
+            for row in y..y + h {
+                let start = row as usize * width as usize + x as usize;
+                data[start..start + w as usize].fill(0);
+            }
+        }
+
+        let mut encoder = Encoder::new(writer, width, height);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.set_color(ColorType::Grayscale);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .map_err(|error| match error {
+                png::EncodingError::IoError(error) => Error::Io(error),
+                error => Error::Io(io::Error::other(error)),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    // Cells reachable from the first one and the number of passages
+    fn explore(maze: &Maze) -> (usize, usize) {
+        let mut seen = vec![false; maze.width * maze.height];
+        let mut stack = vec![0];
+        seen[0] = true;
+        while let Some(cell) = stack.pop() {
+            for neighbour in maze.neighbours(cell) {
+                if !seen[neighbour] && maze.is_open(cell, neighbour) {
+                    seen[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+        let passages = maze
+            .east
+            .iter()
+            .chain(&maze.south)
+            .filter(|open| **open)
+            .count();
+        (seen.iter().filter(|seen| **seen).count(), passages)
+    }
+
+    #[test]
+    fn perfect() {
+        for algorithm in [
+            Algorithm::Backtracker,
+            Algorithm::Prim,
+            Algorithm::Kruskal,
+            Algorithm::Wilson,
+        ] {
+            let mut rng = ChaCha20Rng::seed_from_u64(42);
+            let maze = Maze::generate(&mut rng, 12, 7, algorithm).unwrap();
+
+            // Connected with exactly one path between cells, a spanning tree
+            assert_eq!(explore(&maze), (84, 83), "{:?}", algorithm);
+
+            let column = Maze::generate(&mut rng, 1, 5, algorithm).unwrap();
+            assert_eq!(explore(&column), (5, 4));
+        }
+    }
+
+    #[test]
+    fn braided() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let mut maze = Maze::generate(&mut rng, 10, 10, Algorithm::Backtracker).unwrap();
+        maze.braid(&mut rng, 1.0).unwrap();
+
+        assert!((0..100).all(|cell| maze.exits(cell) >= 2));
+        assert!(explore(&maze).1 > 99);
+        assert!(maze.braid(&mut rng, 1.5).is_err());
+    }
+
+    #[test]
+    fn renders() {
+        let mut maze = Maze::new(2, 1).unwrap();
+        maze.open(0, 1);
+
+        assert_eq!(maze.ascii(), "##########\n##      ##\n##########\n");
+        assert_eq!(
+            maze.rectangles(32, 16).unwrap(),
+            vec![
+                [0, 0, 112, 16],
+                [0, 16, 16, 48],
+                [96, 16, 16, 48],
+                [16, 48, 80, 16]
+            ]
+        );
+
+        let mut png = Vec::new();
+        maze.encode(&mut png, 4, 2).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        assert!(Maze::new(0, 4).is_err());
+        assert!(maze.encode(&mut png, 0, 2).is_err());
+        assert!(maze.rectangles(4_000_000_000, 16).is_err());
+        assert!(maze.rectangles(32, 0).is_err());
+    }
+}
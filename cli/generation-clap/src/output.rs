@@ -0,0 +1,230 @@
+use clap::ValueEnum;
+use std::fmt;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    /// One record per line, fields separated by tabs
+    #[default]
+    Plain,
+    /// JSON array of objects
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Comma separated values with a header row
+    Csv,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Unsigned(u64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Unsigned(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Integer(value.into())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Value::Unsigned(value)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Unsigned(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+fn json(value: &Value) -> String {
+    match value {
+        Value::Float(value) if !value.is_finite() => "null".into(),
+        Value::String(value) => serde_json::to_string(value).unwrap_or_default(),
+        value => value.to_string(),
+    }
+}
+
+fn csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+// Streams records to the writer, nothing is buffered beyond the writer itself
+pub struct Output<W: Write> {
+    writer: W,
+    format: Format,
+    keys: Vec<String>,
+    records: usize,
+}
+
+impl<W: Write> Output<W> {
+    // The CSV header is written here, so it is there even without records
+    pub fn new(mut writer: W, format: Format, fields: Vec<String>) -> io::Result<Self> {
+        if format == Format::Csv {
+            let header = fields
+                .iter()
+                .map(|name| csv(name))
+                .collect::<Vec<_>>()
+                .join(",");
+            writeln!(writer, "{}", header)?;
+        }
+        let keys = fields
+            .iter()
+            .map(|name| serde_json::to_string(name).unwrap_or_default())
+            .collect();
+        Ok(Self {
+            writer,
+            format,
+            keys,
+            records: 0,
+        })
+    }
+    fn object(&mut self, record: &[Value]) -> io::Result<()> {
+        write!(self.writer, "{{")?;
+        for (index, (name, value)) in self.keys.iter().zip(record).enumerate() {
+            if index > 0 {
+                write!(self.writer, ",")?;
+            }
+            write!(self.writer, "{}:{}", name, json(value))?;
+        }
+        write!(self.writer, "}}")
+    }
+    pub fn write(&mut self, record: &[Value]) -> io::Result<()> {
+        match self.format {
+            Format::Plain => {
+                let line = record
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\t");
+                writeln!(self.writer, "{}", line)?;
+            }
+            Format::Json => {
+                write!(self.writer, "{}", if self.records == 0 { "[" } else { "," })?;
+                self.object(record)?;
+            }
+            Format::Ndjson => {
+                self.object(record)?;
+                writeln!(self.writer)?;
+            }
+            Format::Csv => {
+                let line = record
+                    .iter()
+                    .map(|value| csv(&value.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                writeln!(self.writer, "{}", line)?;
+            }
+        }
+        self.records += 1;
+        Ok(())
+    }
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.format == Format::Json {
+            writeln!(
+                self.writer,
+                "{}",
+                if self.records == 0 { "[]" } else { "]" }
+            )?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render(format: Format, records: &[Vec<Value>]) -> String {
+        let fields = vec!["id".into(), "name".into()];
+        let mut output = Output::new(vec![], format, fields).unwrap();
+        for record in records {
+            output.write(record).unwrap();
+        }
+        String::from_utf8(output.finish().unwrap()).unwrap()
+    }
+
+    fn records() -> Vec<Vec<Value>> {
+        vec![
+            vec![1u64.into(), String::from("a,\"b\"").into()],
+            vec![2u64.into(), String::from("c").into()],
+        ]
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(render(Format::Plain, &records()), "1\ta,\"b\"\n2\tc\n");
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            render(Format::Json, &records()),
+            "[{\"id\":1,\"name\":\"a,\\\"b\\\"\"},{\"id\":2,\"name\":\"c\"}]\n"
+        );
+        assert_eq!(render(Format::Json, &[]), "[]\n");
+    }
+
+    #[test]
+    fn ndjson() {
+        assert_eq!(
+            render(Format::Ndjson, &[vec![f64::NAN.into()]]),
+            "{\"id\":null}\n"
+        );
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            render(Format::Csv, &records()),
+            "id,name\n1,\"a,\"\"b\"\"\"\n2,c\n"
+        );
+        assert_eq!(render(Format::Csv, &[]), "id,name\n");
+    }
+}
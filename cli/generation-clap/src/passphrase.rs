@@ -0,0 +1,161 @@
+use crate::error::Error;
+use clap::ValueEnum;
+use rand::Rng;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// BIP39 English word list (CC0), 2048 words or 11 bits per word
+pub const WORDS: &str = include_str!("words.txt");
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Capitalization {
+    /// Keep words as listed
+    #[default]
+    None,
+    /// Capitalize the first letter of every word
+    First,
+    /// Uppercase every word
+    All,
+    /// Capitalize the first letter of a random half of the words
+    Random,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Passphrase {
+    words: Vec<String>,
+    length: usize,
+    separator: String,
+    capitalization: Capitalization,
+    digits: usize,
+}
+
+// One word per line, for EFF style lists the dice numbers before the word are skipped
+pub fn words(file: Option<&Path>) -> Result<Vec<String>, Error> {
+    let content = match file {
+        Some(file) => fs::read_to_string(file)?,
+        None => WORDS.into(),
+    };
+    let mut seen = HashSet::new();
+    Ok(content
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .filter(|word| seen.insert(*word))
+        .map(String::from)
+        .collect())
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl Passphrase {
+    pub fn new(
+        words: Vec<String>,
+        length: usize,
+        separator: &str,
+        capitalization: Capitalization,
+        digits: usize,
+    ) -> Result<Self, Error> {
+        if words.len() < 2 {
+            return Err(Error::parameter(
+                "word list needs at least 2 distinct words",
+            ));
+        }
+        if length == 0 {
+            return Err(Error::parameter("passphrase needs at least 1 word"));
+        }
+        Ok(Self {
+            words,
+            length,
+            separator: separator.into(),
+            capitalization,
+            digits,
+        })
+    }
+    pub fn bits_per_word(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+    // Digit positions are not counted, which keeps the estimate conservative
+    pub fn entropy(&self) -> f64 {
+        let words = self.length as f64 * self.bits_per_word();
+        let digits = self.digits as f64 * 10f64.log2();
+        let capitalization = match self.capitalization {
+            Capitalization::Random => self.length as f64,
+            _ => 0.0,
+        };
+        words + digits + capitalization
+    }
+    // `gen_range` samples by rejection, so word choice has no modulo bias
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let mut words = (0..self.length)
+            .map(|_| {
+                let word = &self.words[rng.gen_range(0..self.words.len())];
+                match self.capitalization {
+                    Capitalization::None => word.clone(),
+                    Capitalization::First => capitalize(word),
+                    Capitalization::All => word.to_uppercase(),
+                    Capitalization::Random if rng.gen::<bool>() => capitalize(word),
+                    Capitalization::Random => word.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..self.digits {
+            let index = rng.gen_range(0..words.len());
+            let digit = char::from(b'0' + rng.gen_range(0..10));
+            words[index].push(digit);
+        }
+        words.join(&self.separator)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    fn list() -> Vec<String> {
+        vec!["alpha".into(), "beta".into()]
+    }
+
+    #[test]
+    fn embedded() {
+        let words = words(None).unwrap();
+
+        assert_eq!(words.len(), 2048);
+        assert_eq!(words[0], "abandon");
+    }
+
+    #[test]
+    fn generate() {
+        let mut rng = StepRng::new(0, 0);
+        let passphrase = Passphrase::new(list(), 3, "-", Capitalization::First, 0).unwrap();
+
+        assert_eq!(passphrase.generate(&mut rng), "Alpha-Alpha-Alpha");
+    }
+
+    #[test]
+    fn digits() {
+        let mut rng = StepRng::new(0, 0);
+        let passphrase = Passphrase::new(list(), 2, " ", Capitalization::All, 2).unwrap();
+
+        assert_eq!(passphrase.generate(&mut rng), "ALPHA00 ALPHA");
+    }
+
+    #[test]
+    fn entropy() {
+        let passphrase = Passphrase::new(list(), 4, " ", Capitalization::Random, 1).unwrap();
+
+        assert!((passphrase.entropy() - (4.0 + 10f64.log2() + 4.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Passphrase::new(vec!["one".into()], 4, " ", Capitalization::None, 0).is_err());
+        assert!(Passphrase::new(list(), 0, " ", Capitalization::None, 0).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::error::Error;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+pub const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+pub const DIGITS: &str = "0123456789";
+pub const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+pub const AMBIGUOUS: &str = "0Oo1lI|";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Policy {
+    pub length: usize,
+    pub lower: usize,
+    pub upper: usize,
+    pub digits: usize,
+    pub symbols: usize,
+    pub ambiguous: bool,
+    pub repeats: bool,
+    pub entropy: f64,
+}
+
+// A checked policy, holds the character classes and their minimum counts
+#[derive(Clone, Debug, PartialEq)]
+pub struct Password {
+    length: usize,
+    classes: Vec<(Vec<char>, usize)>,
+    pool: Vec<char>,
+    repeats: bool,
+}
+
+impl Password {
+    pub fn new(policy: &Policy) -> Result<Self, Error> {
+        let characters = |set: &str| {
+            set.chars()
+                .filter(|c| policy.ambiguous || !AMBIGUOUS.contains(*c))
+                .collect::<Vec<_>>()
+        };
+
+        let classes = vec![
+            (characters(LOWER), policy.lower),
+            (characters(UPPER), policy.upper),
+            (characters(DIGITS), policy.digits),
+            (characters(SYMBOLS), policy.symbols),
+        ];
+        let pool = classes
+            .iter()
+            .flat_map(|(set, _)| set.iter().copied())
+            .collect::<Vec<_>>();
+
+        if !policy.entropy.is_finite() {
+            return Err(Error::parameter("entropy floor must be a finite number"));
+        }
+
+        let required = classes.iter().map(|(_, minimum)| minimum).sum::<usize>();
+        if required > policy.length {
+            return Err(Error::parameter(format!(
+                "policy requires at least {} characters but length is {}",
+                required, policy.length
+            )));
+        }
+        if !policy.repeats {
+            if let Some((set, minimum)) = classes.iter().find(|(set, minimum)| minimum > &set.len())
+            {
+                return Err(Error::parameter(format!(
+                    "policy requires {} distinct characters from a class of {}",
+                    minimum,
+                    set.len()
+                )));
+            }
+            if policy.length > pool.len() {
+                return Err(Error::parameter(format!(
+                    "length {} exceeds the {} distinct characters available without repeats",
+                    policy.length,
+                    pool.len()
+                )));
+            }
+        }
+
+        let password = Self {
+            length: policy.length,
+            classes,
+            pool,
+            repeats: policy.repeats,
+        };
+
+        if password.entropy() < policy.entropy {
+            return Err(Error::parameter(format!(
+                "estimated entropy of {:.1} bits is below the floor of {:.1} bits",
+                password.entropy(),
+                policy.entropy
+            )));
+        }
+
+        Ok(password)
+    }
+    // Conservative bits, forced draws count at their class size and the shuffle is ignored
+    pub fn entropy(&self) -> f64 {
+        let repeats = self.repeats;
+        let bits = |size: usize, draws: usize| -> f64 {
+            (0..draws)
+                .map(|i| ((size - if repeats { 0 } else { i }) as f64).log2())
+                .sum()
+        };
+        let required = self
+            .classes
+            .iter()
+            .map(|(_, minimum)| minimum)
+            .sum::<usize>();
+        let forced = self
+            .classes
+            .iter()
+            .map(|(set, minimum)| bits(set.len(), *minimum))
+            .sum::<f64>();
+        let size = self.pool.len() - if repeats { 0 } else { required };
+        forced + bits(size, self.length - required)
+    }
+    // Always draws from the operating system generator, seeds do not apply
+    pub fn generate(&self) -> String {
+        self.sample(&mut OsRng)
+    }
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let mut characters = Vec::with_capacity(self.length);
+
+        let mut pick = |set: &[char], characters: &mut Vec<char>| {
+            let candidates = set
+                .iter()
+                .filter(|c| self.repeats || !characters.contains(*c))
+                .collect::<Vec<_>>();
+            characters.push(**candidates.choose(rng).expect("policy is satisfiable"));
+        };
+
+        for (set, minimum) in &self.classes {
+            for _ in 0..*minimum {
+                pick(set, &mut characters);
+            }
+        }
+        while characters.len() < self.length {
+            pick(&self.pool, &mut characters);
+        }
+
+        characters.shuffle(rng);
+        characters.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn policy() -> Policy {
+        Policy {
+            length: 16,
+            lower: 1,
+            upper: 1,
+            digits: 1,
+            symbols: 1,
+            ambiguous: true,
+            repeats: true,
+            entropy: 0.0,
+        }
+    }
+
+    #[test]
+    fn classes() {
+        let password = Password::new(&Policy {
+            lower: 4,
+            upper: 4,
+            digits: 4,
+            symbols: 4,
+            ..policy()
+        })
+        .unwrap();
+
+        for _ in 0..32 {
+            let value = password.generate();
+            let count = |set: &str| value.chars().filter(|c| set.contains(*c)).count();
+
+            assert_eq!(value.chars().count(), 16);
+            assert_eq!(count(LOWER), 4);
+            assert_eq!(count(UPPER), 4);
+            assert_eq!(count(DIGITS), 4);
+            assert_eq!(count(SYMBOLS), 4);
+        }
+    }
+
+    #[test]
+    fn ambiguous() {
+        let password = Password::new(&Policy {
+            length: 64,
+            ambiguous: false,
+            ..policy()
+        })
+        .unwrap();
+
+        for _ in 0..32 {
+            assert!(!password.generate().contains(|c| AMBIGUOUS.contains(c)));
+        }
+    }
+
+    #[test]
+    fn repeats() {
+        let password = Password::new(&Policy {
+            length: 60,
+            repeats: false,
+            ..policy()
+        })
+        .unwrap();
+
+        for _ in 0..32 {
+            let mut characters = password.generate().chars().collect::<Vec<_>>();
+            characters.sort_unstable();
+            characters.dedup();
+
+            assert_eq!(characters.len(), 60);
+        }
+    }
+
+    #[test]
+    fn entropy() {
+        let password = Password::new(&Policy {
+            length: 10,
+            ..policy()
+        })
+        .unwrap();
+
+        let forced = 2.0 * 26f64.log2() + 10f64.log2() + 32f64.log2();
+        assert!((password.entropy() - forced - 6.0 * 94f64.log2()).abs() < 1e-9);
+
+        let password = Password::new(&Policy {
+            length: 4,
+            lower: 0,
+            upper: 0,
+            symbols: 0,
+            digits: 4,
+            repeats: false,
+            ..policy()
+        })
+        .unwrap();
+        assert!((password.entropy() - (10f64 * 9.0 * 8.0 * 7.0).log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        assert!(Password::new(&Policy {
+            length: 3,
+            ..policy()
+        })
+        .is_err());
+        assert!(Password::new(&Policy {
+            digits: 9,
+            ambiguous: false,
+            repeats: false,
+            ..policy()
+        })
+        .is_err());
+        assert!(Password::new(&Policy {
+            length: 95,
+            repeats: false,
+            ..policy()
+        })
+        .is_err());
+        assert!(Password::new(&Policy {
+            length: 8,
+            entropy: 64.0,
+            ..policy()
+        })
+        .is_err());
+        assert!(Password::new(&Policy {
+            entropy: f64::NAN,
+            ..policy()
+        })
+        .is_err());
+    }
+}
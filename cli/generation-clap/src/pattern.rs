@@ -0,0 +1,471 @@
+use crate::error::Error;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const MAX_REPEAT: usize = 8;
+// Largest count `{n}` or `{n,m}` may spell out
+pub const MAX_COUNT: usize = 10_000;
+// Most characters one generated value may have
+pub const MAX_LENGTH: usize = 1 << 20;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Literal(char),
+    Class(Vec<char>),
+    Sequence(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat(Box<Node>, usize, usize),
+}
+
+// Negated classes, `.` and the shorthand complements draw from printable ASCII
+fn printable() -> impl Iterator<Item = char> {
+    ' '..='~'
+}
+
+fn digits() -> Vec<char> {
+    ('0'..='9').collect()
+}
+
+fn word() -> Vec<char> {
+    ('a'..='z')
+        .chain('A'..='Z')
+        .chain('0'..='9')
+        .chain(['_'])
+        .collect()
+}
+
+// Space and tab only, so generated values stay on one line
+fn space() -> Vec<char> {
+    vec![' ', '\t']
+}
+
+fn complement(set: &[char]) -> Vec<char> {
+    printable().filter(|c| !set.contains(c)).collect()
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    cap: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> Error {
+        Error::parameter(format!(
+            "pattern: {} at position {}",
+            message, self.position
+        ))
+    }
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.position += 1;
+        }
+        c
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, Error> {
+        let mut branches = vec![self.sequence()?];
+        while self.eat('|') {
+            branches.push(self.sequence()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Node::Alternation(branches)
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Node, Error> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '|' | ')' => break,
+                '^' if self.position == 0 => self.position += 1,
+                '$' if self.position == self.chars.len() - 1 => self.position += 1,
+                _ => {
+                    let atom = self.atom()?;
+                    nodes.push(self.quantifier(atom)?);
+                }
+            }
+        }
+        Ok(Node::Sequence(nodes))
+    }
+
+    fn quantifier(&mut self, atom: Node) -> Result<Node, Error> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, self.cap),
+            Some('+') => (1, self.cap.saturating_add(1)),
+            Some('?') => (0, 1),
+            Some('{') => {
+                self.position += 1;
+                let bounds = self.bounds()?;
+                self.position -= 1;
+                bounds
+            }
+            _ => return Ok(atom),
+        };
+        self.position += 1;
+        // Lazy quantifiers generate the same strings
+        self.eat('?');
+        if matches!(self.peek(), Some('*' | '+' | '?' | '{')) {
+            return Err(self.error("nested quantifier is not supported"));
+        }
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    // `{n}`, `{n,}` or `{n,m}` after the opening brace
+    fn bounds(&mut self) -> Result<(usize, usize), Error> {
+        let min = self
+            .number()?
+            .ok_or_else(|| self.error("expected repetition count"))?;
+        let max = if self.eat(',') {
+            self.number()?
+        } else {
+            Some(min)
+        };
+        if min.max(max.unwrap_or(0)) > MAX_COUNT {
+            return Err(self.error(&format!("repetition count is above {}", MAX_COUNT)));
+        }
+        if !self.eat('}') {
+            return Err(self.error("expected `}` to close the repetition"));
+        }
+        let max = max.unwrap_or(min.saturating_add(self.cap));
+        if min > max {
+            return Err(self.error("repetition minimum is above its maximum"));
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Result<Option<usize>, Error> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Ok(None);
+        }
+        self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .map(Some)
+            .map_err(|_| self.error("repetition count is too large"))
+    }
+
+    fn atom(&mut self) -> Result<Node, Error> {
+        match self.next() {
+            Some('(') => {
+                if self.eat('?') && !self.eat(':') {
+                    self.position -= 1;
+                    return Err(self.error("only `(?:` group modifiers are supported"));
+                }
+                let node = self.alternation()?;
+                if !self.eat(')') {
+                    return Err(self.error("expected `)` to close the group"));
+                }
+                Ok(node)
+            }
+            Some('[') => self.class(),
+            Some('.') => Ok(Node::Class(printable().collect())),
+            Some('\\') => match self.escape()? {
+                Escape::Char(c) => Ok(Node::Literal(c)),
+                Escape::Set(set) => Ok(Node::Class(set)),
+            },
+            Some(c @ ('*' | '+' | '?' | '{')) => {
+                self.position -= 1;
+                Err(self.error(&format!("`{}` has nothing to repeat", c)))
+            }
+            Some(c @ (']' | '}')) => {
+                self.position -= 1;
+                Err(self.error(&format!("unescaped `{}`", c)))
+            }
+            Some(c @ ('^' | '$')) => {
+                self.position -= 1;
+                Err(self.error(&format!("anchor `{}` is only supported at the ends", c)))
+            }
+            Some(c) => Ok(Node::Literal(c)),
+            None => Err(self.error("unexpected end of pattern")),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Escape, Error> {
+        let escape = match self.next() {
+            Some('d') => Escape::Set(digits()),
+            Some('D') => Escape::Set(complement(&digits())),
+            Some('w') => Escape::Set(word()),
+            Some('W') => Escape::Set(complement(&word())),
+            Some('s') => Escape::Set(space()),
+            Some('S') => Escape::Set(complement(&space())),
+            Some('n') => Escape::Char('\n'),
+            Some('t') => Escape::Char('\t'),
+            Some('r') => Escape::Char('\r'),
+            Some('x') => {
+                let hex = self.chars.get(self.position..self.position + 2);
+                let c = hex
+                    .map(|hex| hex.iter().collect::<String>())
+                    .and_then(|hex| u32::from_str_radix(&hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("expected two hex digits after `\\x`"))?;
+                self.position += 2;
+                Escape::Char(c)
+            }
+            Some(c) if c.is_ascii_punctuation() || c == ' ' => Escape::Char(c),
+            Some(c) => {
+                self.position -= 1;
+                return Err(self.error(&format!("escape `\\{}` is not supported", c)));
+            }
+            None => return Err(self.error("pattern ends with `\\`")),
+        };
+        Ok(escape)
+    }
+
+    fn class(&mut self) -> Result<Node, Error> {
+        let negated = self.eat('^');
+        let mut set = Vec::new();
+        let mut first = true;
+        loop {
+            let item = match self.next() {
+                None => return Err(self.error("expected `]` to close the class")),
+                Some(']') if !first => break,
+                Some('\\') => self.escape()?,
+                Some(c) => Escape::Char(c),
+            };
+            first = false;
+            match item {
+                Escape::Set(items) => set.extend(items),
+                Escape::Char(start) => {
+                    let range = self.peek() == Some('-')
+                        && self.chars.get(self.position + 1).is_some_and(|c| *c != ']');
+                    if !range {
+                        set.push(start);
+                        continue;
+                    }
+                    self.position += 1;
+                    let end = match self.next() {
+                        Some('\\') => match self.escape()? {
+                            Escape::Char(c) => c,
+                            Escape::Set(_) => {
+                                return Err(self.error("class shorthand cannot end a range"))
+                            }
+                        },
+                        Some(c) => c,
+                        None => return Err(self.error("expected `]` to close the class")),
+                    };
+                    if start > end {
+                        return Err(self.error(&format!("range `{}-{}` is reversed", start, end)));
+                    }
+                    set.extend(start..=end);
+                }
+            }
+        }
+        set.sort_unstable();
+        set.dedup();
+        if negated {
+            set = complement(&set);
+        }
+        if set.is_empty() {
+            return Err(self.error("class matches no characters"));
+        }
+        Ok(Node::Class(set))
+    }
+}
+
+enum Escape {
+    Char(char),
+    Set(Vec<char>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    node: Node,
+}
+
+impl Pattern {
+    // `*`, `+` and open ranges repeat at most `cap` more times than their minimum
+    pub fn new(source: &str, cap: usize) -> Result<Self, Error> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            position: 0,
+            cap,
+        };
+        let node = parser.alternation()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("unmatched `)`"));
+        }
+        if longest(&node) > MAX_LENGTH {
+            return Err(Error::parameter(format!(
+                "pattern: values could be longer than {} characters",
+                MAX_LENGTH
+            )));
+        }
+        Ok(Self { node })
+    }
+    // Most characters one value can have
+    pub fn longest(&self) -> usize {
+        longest(&self.node)
+    }
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let mut value = String::new();
+        generate(&self.node, rng, &mut value);
+        value
+    }
+}
+
+fn longest(node: &Node) -> usize {
+    match node {
+        Node::Literal(_) | Node::Class(_) => 1,
+        Node::Sequence(nodes) => nodes
+            .iter()
+            .fold(0, |total, node| total.saturating_add(longest(node))),
+        Node::Alternation(branches) => branches.iter().map(longest).max().unwrap_or(0),
+        Node::Repeat(node, _, max) => longest(node).saturating_mul(*max),
+    }
+}
+
+fn generate<R: Rng + ?Sized>(node: &Node, rng: &mut R, value: &mut String) {
+    match node {
+        Node::Literal(c) => value.push(*c),
+        Node::Class(set) => value.push(*set.choose(rng).expect("class is not empty")),
+        Node::Sequence(nodes) => {
+            for node in nodes {
+                generate(node, rng, value);
+            }
+        }
+        Node::Alternation(branches) => generate(
+            branches.choose(rng).expect("alternation has branches"),
+            rng,
+            value,
+        ),
+        Node::Repeat(node, min, max) => {
+            for _ in 0..rng.gen_range(*min..=*max) {
+                generate(node, rng, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn samples(source: &str) -> Vec<String> {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let pattern = Pattern::new(source, MAX_REPEAT).unwrap();
+        (0..64).map(|_| pattern.generate(&mut rng)).collect()
+    }
+
+    #[test]
+    fn identifiers() {
+        for value in samples(r"^[A-Z]{3}-\d{4}$") {
+            let (letters, digits) = value.split_once('-').unwrap();
+
+            assert_eq!(letters.len(), 3);
+            assert!(letters.chars().all(|c| c.is_ascii_uppercase()));
+            assert_eq!(digits.len(), 4);
+            assert!(digits.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn alternation() {
+        let values = samples("(?:cat|dog)s?|bird");
+
+        assert!(values
+            .iter()
+            .all(|value| ["cat", "cats", "dog", "dogs", "bird"].contains(&value.as_str())));
+        assert!(values.iter().any(|value| value == "bird"));
+    }
+
+    #[test]
+    fn classes() {
+        for value in samples(r"[^a-z\d]\.[-_x]\x41[a\]]+?") {
+            let chars = value.chars().collect::<Vec<_>>();
+
+            assert!(!chars[0].is_ascii_lowercase() && !chars[0].is_ascii_digit());
+            assert_eq!(chars[1], '.');
+            assert!("-_x".contains(chars[2]));
+            assert_eq!(chars[3], 'A');
+            assert!(chars[4..].iter().all(|c| *c == 'a' || *c == ']'));
+        }
+    }
+
+    #[test]
+    fn repetition_cap() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let pattern = Pattern::new("a*b{2,}", 3).unwrap();
+
+        for _ in 0..64 {
+            let value = pattern.generate(&mut rng);
+            let a = value.chars().filter(|c| *c == 'a').count();
+            let b = value.chars().filter(|c| *c == 'b').count();
+
+            assert!(a <= 3);
+            assert!((2..=5).contains(&b));
+        }
+    }
+
+    #[test]
+    fn plus_cap() {
+        let pattern = Pattern::new("a+", 3).unwrap();
+        assert_eq!(pattern.longest(), 4);
+        assert_eq!(Pattern::new("a+", 0).unwrap().longest(), 1);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let lengths = (0..256)
+            .map(|_| pattern.generate(&mut rng).len())
+            .collect::<Vec<_>>();
+        assert!(lengths.iter().all(|length| (1..=4).contains(length)));
+        assert!(lengths.contains(&4));
+    }
+
+    #[test]
+    fn unsupported() {
+        for source in [
+            "(?=a)",
+            r"(a)\1",
+            "[z-a]",
+            "a{3,2}",
+            "(ab",
+            "ab)",
+            "*a",
+            "a**",
+            "[abc",
+            "a{x}",
+            "a{18446744073709551615,}",
+            "a{10001}",
+            "a{1,100000}",
+            "((a{10000}){10000}){10000}",
+            r"\p{L}",
+            "a^b",
+            "[^\x20-~]",
+        ] {
+            assert!(Pattern::new(source, MAX_REPEAT).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn end_position() {
+        for source in [r"ab\", "[ab", "(a"] {
+            let error = Pattern::new(source, MAX_REPEAT).unwrap_err().to_string();
+            assert!(
+                error.ends_with(&format!("at position {}", source.len())),
+                "{}",
+                error
+            );
+        }
+    }
+}
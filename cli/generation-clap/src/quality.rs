@@ -0,0 +1,404 @@
+use crate::error::Error;
+use crate::generation::{custom, float, range};
+use rand::Rng;
+use std::collections::HashMap;
+use std::f64::consts::SQRT_2;
+use std::io::{self, Write};
+use unicode_segmentation::UnicodeSegmentation;
+
+const ITERATIONS: usize = 500;
+const EPSILON: f64 = 1e-14;
+const WIDTH: usize = 50;
+// Graphemes kept in memory by the charset checks
+const MAX_POSITIONS: usize = 100_000_000;
+
+// Lanczos approximation (g = 7, n = 9), accurate to about 15 digits for x > 0
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+// Regularized lower incomplete gamma P(a, x) by its power series, converges for x < a + 1
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1.0 / a;
+    let mut sum = term;
+    for n in 1..ITERATIONS {
+        term *= x / (a + n as f64);
+        sum += term;
+        if term.abs() < sum.abs() * EPSILON {
+            break;
+        }
+    }
+    sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+// Regularized upper incomplete gamma Q(a, x) by Lentz's continued fraction, for x >= a + 1
+fn gamma_fraction(a: f64, x: f64) -> f64 {
+    let tiny = f64::MIN_POSITIVE / EPSILON;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for n in 1..ITERATIONS {
+        let an = -(n as f64) * (n as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+// Regularized upper incomplete gamma Q(a, x) = 1 - P(a, x)
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        1.0
+    } else if x < a + 1.0 {
+        1.0 - gamma_series(a, x)
+    } else {
+        gamma_fraction(a, x)
+    }
+}
+
+// Complementary error function, erfc(x) = Q(1/2, x²) for x >= 0
+pub fn erfc(x: f64) -> f64 {
+    if x < 0.0 {
+        2.0 - gamma_q(0.5, x * x)
+    } else {
+        gamma_q(0.5, x * x)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    pub name: String,
+    pub statistic: f64,
+    pub p: f64,
+    // Observed counts with their labels, rendered by `report`
+    pub buckets: Vec<(String, u64)>,
+}
+
+impl Check {
+    pub fn passed(&self, alpha: f64) -> bool {
+        self.p >= alpha
+    }
+}
+
+// Pearson's test with k - 1 degrees of freedom
+pub fn chi_squared(observed: &[u64], expected: &[f64]) -> (f64, f64) {
+    let statistic = observed
+        .iter()
+        .zip(expected)
+        .map(|(&o, &e)| (o as f64 - e).powi(2) / e)
+        .sum::<f64>();
+    let freedom = (observed.len() - 1) as f64;
+    (statistic, gamma_q(freedom / 2.0, statistic / 2.0))
+}
+
+// Wald-Wolfowitz test on a two valued sequence, p-value is two sided
+pub fn runs(sequence: &[bool]) -> (f64, f64) {
+    let n1 = sequence.iter().filter(|value| **value).count() as f64;
+    let n2 = sequence.len() as f64 - n1;
+    let n = n1 + n2;
+    if n1 == 0.0 || n2 == 0.0 {
+        return (f64::INFINITY, 0.0);
+    }
+    let runs = 1 + sequence
+        .windows(2)
+        .filter(|pair| pair[0] != pair[1])
+        .count();
+    let mean = 2.0 * n1 * n2 / n + 1.0;
+    let variance = 2.0 * n1 * n2 * (2.0 * n1 * n2 - n) / (n * n * (n - 1.0));
+    let z = (runs as f64 - mean) / variance.sqrt();
+    (z, erfc(z.abs() / SQRT_2))
+}
+
+// Integer range split into up to `buckets` near equal bins, expected counts follow bin widths
+pub fn uniformity<R: Rng + ?Sized>(
+    rng: &mut R,
+    samples: u64,
+    start: i64,
+    end: i64,
+    buckets: usize,
+) -> Result<Check, Error> {
+    if start >= end {
+        return Err(Error::parameter("start must be below end"));
+    }
+    let span = (end as i128 - start as i128) as u128;
+    let k = (buckets as u128).min(span) as usize;
+    let bound = |i: usize| start as i128 + ((i as u128 * span).div_ceil(k as u128)) as i128;
+
+    let mut observed = vec![0; k];
+    for _ in 0..samples {
+        let offset = (range(rng, start, end) as i128 - start as i128) as u128;
+        observed[(offset * k as u128 / span) as usize] += 1;
+    }
+    let expected = (0..k)
+        .map(|i| (bound(i + 1) - bound(i)) as f64 / span as f64 * samples as f64)
+        .collect::<Vec<_>>();
+
+    let (statistic, p) = chi_squared(&observed, &expected);
+    let buckets = (0..k)
+        .map(|i| (format!("[{}, {})", bound(i), bound(i + 1)), observed[i]))
+        .collect();
+
+    Ok(Check {
+        name: format!("range uniformity [{}, {})", start, end),
+        statistic,
+        p,
+        buckets,
+    })
+}
+
+pub fn histogram<R: Rng + ?Sized>(rng: &mut R, samples: u64, buckets: usize) -> Check {
+    let mut observed = vec![0; buckets];
+    for _ in 0..samples {
+        observed[((float(rng) * buckets as f64) as usize).min(buckets - 1)] += 1;
+    }
+    let expected = vec![samples as f64 / buckets as f64; buckets];
+
+    let (statistic, p) = chi_squared(&observed, &expected);
+    let width = 1.0 / buckets as f64;
+    let buckets = (0..buckets)
+        .map(|i| {
+            let label = format!("[{:.3}, {:.3})", i as f64 * width, (i + 1) as f64 * width);
+            (label, observed[i])
+        })
+        .collect();
+
+    Check {
+        name: "float histogram [0, 1)".into(),
+        statistic,
+        p,
+        buckets,
+    }
+}
+
+// Positions of the generated graphemes in the charset
+fn positions<R: Rng + ?Sized>(
+    rng: &mut R,
+    samples: u64,
+    length: usize,
+    characters: &[String],
+) -> Result<Vec<usize>, Error> {
+    if characters.len() < 2 {
+        return Err(Error::parameter("charset needs at least 2 characters"));
+    }
+    let index = characters
+        .iter()
+        .enumerate()
+        .map(|(i, character)| (character.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let total = usize::try_from(samples)
+        .ok()
+        .and_then(|samples| samples.checked_mul(length))
+        .filter(|total| *total <= MAX_POSITIONS)
+        .ok_or_else(|| {
+            Error::parameter(format!(
+                "samples times length must not be above {}",
+                MAX_POSITIONS
+            ))
+        })?;
+    let mut positions = Vec::with_capacity(total);
+    for _ in 0..samples {
+        for grapheme in custom(rng, length, characters).graphemes(true) {
+            positions.push(*index.get(grapheme).ok_or_else(|| {
+                Error::parameter("charset characters merge into other graphemes when joined")
+            })?);
+        }
+    }
+    Ok(positions)
+}
+
+pub fn frequency<R: Rng + ?Sized>(
+    rng: &mut R,
+    samples: u64,
+    length: usize,
+    characters: &[String],
+) -> Result<Check, Error> {
+    let positions = positions(rng, samples, length, characters)?;
+    let mut observed = vec![0; characters.len()];
+    for position in &positions {
+        observed[*position] += 1;
+    }
+    let expected = vec![positions.len() as f64 / characters.len() as f64; characters.len()];
+
+    let (statistic, p) = chi_squared(&observed, &expected);
+    let buckets = characters
+        .iter()
+        .zip(&observed)
+        .map(|(character, count)| (format!("{:?}", character), *count))
+        .collect();
+
+    Ok(Check {
+        name: "custom character frequency".into(),
+        statistic,
+        p,
+        buckets,
+    })
+}
+
+// Characters are split into the first and second half of the charset
+pub fn serial<R: Rng + ?Sized>(
+    rng: &mut R,
+    samples: u64,
+    length: usize,
+    characters: &[String],
+) -> Result<Check, Error> {
+    let half = characters.len() / 2;
+    let sequence = positions(rng, samples, length, characters)?
+        .into_iter()
+        .map(|position| position < half)
+        .collect::<Vec<_>>();
+
+    let (statistic, p) = runs(&sequence);
+
+    Ok(Check {
+        name: "custom runs".into(),
+        statistic,
+        p,
+        buckets: Vec::new(),
+    })
+}
+
+pub fn report<W: Write>(
+    writer: &mut W,
+    checks: &[Check],
+    alpha: f64,
+    bars: bool,
+) -> io::Result<()> {
+    for check in checks {
+        writeln!(
+            writer,
+            "{:<32} statistic {:>12.4}  p-value {:.6}  {}",
+            check.name,
+            check.statistic,
+            check.p,
+            if check.passed(alpha) { "PASS" } else { "FAIL" }
+        )?;
+        if bars && !check.buckets.is_empty() {
+            histogram_bars(writer, &check.buckets)?;
+        }
+    }
+    Ok(())
+}
+
+fn histogram_bars<W: Write>(writer: &mut W, buckets: &[(String, u64)]) -> io::Result<()> {
+    let label = buckets
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    let maximum = buckets
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (name, count) in buckets {
+        let bar = (*count as f64 / maximum as f64 * WIDTH as f64).round() as usize;
+        writeln!(
+            writer,
+            "  {:>label$} {:>10} {}",
+            name,
+            count,
+            "#".repeat(bar)
+        )?;
+    }
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::charset::parse;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn functions() {
+        assert!(close(ln_gamma(5.0), 24f64.ln()));
+        assert!(close(gamma_q(1.0, 0.5), (-0.5f64).exp()));
+        assert!(close(gamma_q(1.0, 3.0), (-3.0f64).exp()));
+        assert!(close(erfc(0.0), 1.0));
+        assert!(close(erfc(1.0), 0.157_299_207_050_285_13));
+        assert!(close(erfc(-1.0), 1.842_700_792_949_715));
+    }
+
+    #[test]
+    fn statistics() {
+        let (statistic, p) = chi_squared(&[10, 10, 10], &[10.0, 10.0, 10.0]);
+        assert!(close(statistic, 0.0));
+        assert!(close(p, 1.0));
+
+        let (statistic, p) = chi_squared(&[30, 0], &[15.0, 15.0]);
+        assert!(close(statistic, 30.0));
+        assert!(p < 1e-6);
+
+        let alternating = (0..100).map(|i| i % 2 == 0).collect::<Vec<_>>();
+        assert!(runs(&alternating).1 < 1e-6);
+        assert_eq!(runs(&[true, true]).1, 0.0);
+    }
+
+    #[test]
+    fn generators_pass() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let characters = parse("a-fä👍🏽").unwrap();
+
+        assert!(uniformity(&mut rng, 10_000, -5, 7, 10)
+            .unwrap()
+            .passed(0.001));
+        assert!(histogram(&mut rng, 10_000, 10).passed(0.001));
+        assert!(frequency(&mut rng, 1_000, 16, &characters)
+            .unwrap()
+            .passed(0.001));
+        assert!(serial(&mut rng, 1_000, 16, &characters)
+            .unwrap()
+            .passed(0.001));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+
+        assert!(uniformity(&mut rng, 100, 3, 3, 10).is_err());
+        assert!(frequency(&mut rng, 100, 16, &parse("a").unwrap()).is_err());
+        let merging = vec!["a".to_string(), "\u{301}".to_string()];
+        assert!(frequency(&mut rng, 100, 16, &merging).is_err());
+        assert!(frequency(&mut rng, u64::MAX, 16, &parse("ab").unwrap()).is_err());
+        assert!(serial(&mut rng, 1 << 30, 1 << 30, &parse("ab").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,518 @@
+use crate::charset;
+use crate::error::Error;
+use crate::generation::{custom, range, round, string};
+use crate::output::Value;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Days, NaiveDate};
+use rand::distributions::{Bernoulli, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Alan", "Alice", "Amara", "Bob", "Carlos", "Chen", "Dana", "Elena", "Emma", "Fatima",
+    "Grace", "Hana", "Ivan", "Jamal", "Julia", "Kenji", "Lars", "Leon", "Lucia", "Maya", "Mei",
+    "Noah", "Nora", "Olga", "Omar", "Priya", "Quinn", "Ravi", "Rosa", "Sam", "Sofia", "Tariq",
+    "Uma", "Victor", "Wei", "Xavier", "Yara", "Yusuf", "Zoe",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Adams", "Bakker", "Brown", "Costa", "Dubois", "Evans", "Fischer", "Garcia", "Hansen", "Ito",
+    "Jansen", "Kim", "Kowalski", "Lee", "Lopez", "Martin", "Meyer", "Nguyen", "Novak", "Okafor",
+    "Patel", "Quinn", "Rossi", "Sato", "Schmidt", "Silva", "Smith", "Tanaka", "Umar", "Visser",
+    "Wang", "Weber", "Xu", "Yilmaz", "Zhang",
+];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+const LOREM: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "ad",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+    "aliquip",
+    "ex",
+    "ea",
+    "commodo",
+    "consequat",
+    "duis",
+    "aute",
+    "irure",
+    "in",
+    "reprehenderit",
+    "voluptate",
+    "velit",
+    "esse",
+    "cillum",
+    "fugiat",
+    "nulla",
+    "pariatur",
+    "excepteur",
+    "sint",
+    "occaecat",
+    "cupidatat",
+    "non",
+    "proident",
+    "sunt",
+    "culpa",
+    "qui",
+    "officia",
+    "deserunt",
+    "mollit",
+    "anim",
+    "id",
+    "est",
+    "laborum",
+];
+
+fn one() -> i64 {
+    1
+}
+
+fn half() -> f64 {
+    0.5
+}
+
+fn words() -> usize {
+    8
+}
+
+fn date_format() -> String {
+    "%Y-%m-%d".into()
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Kind {
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Float {
+        min: f64,
+        max: f64,
+        decimals: Option<usize>,
+    },
+    String {
+        length: usize,
+        charset: Option<String>,
+    },
+    Enum {
+        values: Vec<String>,
+        weights: Option<Vec<f64>>,
+    },
+    Date {
+        after: NaiveDate,
+        before: NaiveDate,
+        #[serde(default = "date_format")]
+        format: String,
+    },
+    Bool {
+        #[serde(default = "half")]
+        probability: f64,
+    },
+    Name {},
+    Email {},
+    Lorem {
+        #[serde(default = "words")]
+        words: usize,
+    },
+    Sequence {
+        #[serde(default)]
+        start: i64,
+        #[serde(default = "one")]
+        step: i64,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Field {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: Kind,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        toml::from_str(source).map_err(|error| Error::parameter(format!("schema: {}", error)))
+    }
+}
+
+enum Generator {
+    Integer(i64, i64),
+    Float(f64, f64, Option<usize>),
+    String(usize, Option<Vec<String>>),
+    Enum(Vec<String>, Option<WeightedIndex<f64>>),
+    Date(NaiveDate, u64, String),
+    Bool(Bernoulli),
+    Name,
+    Email,
+    Lorem(usize),
+    Sequence(Option<i64>, i64),
+}
+
+fn generator(field: &Field, count: u64) -> Result<Generator, Error> {
+    let invalid = |reason: &str| Error::parameter(format!("field `{}`: {}", field.name, reason));
+
+    let generator = match &field.kind {
+        Kind::Integer { min, max } if min > max => return Err(invalid("min is above max")),
+        Kind::Integer { min, max } => Generator::Integer(*min, *max),
+        Kind::Float { min, max, .. } if !(min < max && (max - min).is_finite()) => {
+            return Err(invalid("min and max must be finite with min below max"))
+        }
+        Kind::Float { min, max, decimals } => Generator::Float(*min, *max, *decimals),
+        Kind::String {
+            charset: Some(charset),
+            ..
+        } if charset.is_empty() => return Err(invalid("charset is empty")),
+        Kind::String { length, charset } => {
+            Generator::String(
+                *length,
+                charset.as_deref().map(charset::parse).transpose().map_err(
+                    |error| match error {
+                        Error::Parameter(message) => invalid(&message),
+                        error => error,
+                    },
+                )?,
+            )
+        }
+        Kind::Enum { values, .. } if values.is_empty() => return Err(invalid("values are empty")),
+        Kind::Enum { values, weights } => {
+            let index = match weights {
+                Some(weights) if weights.len() != values.len() => {
+                    return Err(invalid("weights and values differ in length"))
+                }
+                Some(weights) => {
+                    Some(WeightedIndex::new(weights).map_err(|error| invalid(&error.to_string()))?)
+                }
+                None => None,
+            };
+            Generator::Enum(values.clone(), index)
+        }
+        Kind::Date { after, before, .. } if after > before => {
+            return Err(invalid("after is later than before"))
+        }
+        Kind::Date {
+            after,
+            before,
+            format,
+        } => {
+            let mut trial = String::new();
+            let items = StrftimeItems::new(format).collect::<Vec<_>>();
+            if items.iter().any(|item| matches!(item, Item::Error))
+                || write!(trial, "{}", after.format_with_items(items.iter())).is_err()
+            {
+                return Err(invalid(&format!("invalid date format `{}`", format)));
+            }
+            let days = (*before - *after).num_days() as u64;
+            Generator::Date(*after, days, format.clone())
+        }
+        Kind::Bool { probability } => Generator::Bool(
+            Bernoulli::new(*probability)
+                .map_err(|_| invalid("probability must be between 0 and 1"))?,
+        ),
+        Kind::Name {} => Generator::Name,
+        Kind::Email {} => Generator::Email,
+        Kind::Lorem { words } => Generator::Lorem(*words),
+        Kind::Sequence { start, step } => {
+            let last = *start as i128 + *step as i128 * count.saturating_sub(1) as i128;
+            if i64::try_from(last).is_err() {
+                return Err(invalid(&format!(
+                    "sequence leaves the integer range within {} records",
+                    count
+                )));
+            }
+            Generator::Sequence(Some(*start), *step)
+        }
+    };
+
+    Ok(generator)
+}
+
+fn name<R: Rng + ?Sized>(rng: &mut R) -> (&'static str, &'static str) {
+    (
+        FIRST_NAMES.choose(rng).expect("names are not empty"),
+        LAST_NAMES.choose(rng).expect("names are not empty"),
+    )
+}
+
+pub struct Records {
+    fields: Vec<String>,
+    generators: Vec<Generator>,
+}
+
+impl Records {
+    // Sequences are checked to stay in range for `count` records
+    pub fn new(schema: &Schema, count: u64) -> Result<Self, Error> {
+        if schema.fields.is_empty() {
+            return Err(Error::parameter("schema has no fields"));
+        }
+        let mut names = HashSet::new();
+        if let Some(field) = schema
+            .fields
+            .iter()
+            .find(|field| !names.insert(&field.name))
+        {
+            return Err(Error::parameter(format!(
+                "field `{}` is defined more than once",
+                field.name
+            )));
+        }
+        Ok(Self {
+            fields: schema
+                .fields
+                .iter()
+                .map(|field| field.name.clone())
+                .collect(),
+            generators: schema
+                .fields
+                .iter()
+                .map(|field| generator(field, count))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+    pub fn fields(&self) -> Vec<String> {
+        self.fields.clone()
+    }
+    pub fn generate<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Vec<Value> {
+        self.generators
+            .iter_mut()
+            .map(|generator| match generator {
+                Generator::Integer(min, max) => rng.gen_range(*min..=*max).into(),
+                Generator::Float(min, max, None) => range(rng, *min, *max).into(),
+                Generator::Float(min, max, Some(decimals)) => {
+                    round(range(rng, *min, *max), *decimals).into()
+                }
+                Generator::String(length, None) => string(rng, *length).into(),
+                Generator::String(length, Some(charset)) => custom(rng, *length, charset).into(),
+                Generator::Enum(values, None) => values
+                    .choose(rng)
+                    .expect("values are not empty")
+                    .clone()
+                    .into(),
+                Generator::Enum(values, Some(index)) => values[rng.sample(&*index)].clone().into(),
+                Generator::Date(after, days, format) => {
+                    let date = *after + Days::new(rng.gen_range(0..=*days));
+                    date.format(format).to_string().into()
+                }
+                Generator::Bool(bernoulli) => rng.sample(*bernoulli).into(),
+                Generator::Name => {
+                    let (first, last) = name(rng);
+                    format!("{} {}", first, last).into()
+                }
+                Generator::Email => {
+                    let (first, last) = name(rng);
+                    let number = rng.gen_range(1..1000);
+                    let domain = DOMAINS.choose(rng).expect("domains are not empty");
+                    format!("{}.{}{}@{}", first, last, number, domain)
+                        .to_lowercase()
+                        .into()
+                }
+                Generator::Lorem(words) => (0..*words)
+                    .map(|_| *LOREM.choose(rng).expect("words are not empty"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .into(),
+                Generator::Sequence(next, step) => {
+                    let value = next.expect("sequence was checked against the count");
+                    *next = value.checked_add(*step);
+                    value.into()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    const SCHEMA: &str = r#"
+        [[fields]]
+        name = "id"
+        type = "sequence"
+        start = 10
+        step = 5
+
+        [[fields]]
+        name = "age"
+        type = "integer"
+        min = 30
+        max = 30
+
+        [[fields]]
+        name = "status"
+        type = "enum"
+        values = ["active", "inactive"]
+        weights = [0, 1]
+
+        [[fields]]
+        name = "born"
+        type = "date"
+        after = "2000-01-01"
+        before = "2000-01-01"
+        format = "%d/%m/%Y"
+
+        [[fields]]
+        name = "code"
+        type = "string"
+        length = 3
+        charset = "x"
+
+        [[fields]]
+        name = "subscribed"
+        type = "bool"
+        probability = 1.0
+    "#;
+
+    #[test]
+    fn parse() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+
+        assert_eq!(schema.fields.len(), 6);
+        assert_eq!(
+            schema.fields[0],
+            Field {
+                name: "id".into(),
+                kind: Kind::Sequence { start: 10, step: 5 },
+            }
+        );
+    }
+
+    #[test]
+    fn generate() {
+        let mut rng = StepRng::new(0, 1 << 40);
+        let mut records = Records::new(&Schema::parse(SCHEMA).unwrap(), 10).unwrap();
+
+        assert_eq!(
+            records.fields(),
+            vec!["id", "age", "status", "born", "code", "subscribed"]
+        );
+
+        let expected = |id: u64| {
+            vec![
+                Value::Integer(id as i64),
+                Value::Integer(30),
+                Value::String("inactive".into()),
+                Value::String("01/01/2000".into()),
+                Value::String("xxx".into()),
+                Value::Bool(true),
+            ]
+        };
+
+        assert_eq!(records.generate(&mut rng), expected(10));
+        assert_eq!(records.generate(&mut rng), expected(15));
+    }
+
+    #[test]
+    fn generated_text() {
+        let mut rng = StepRng::new(0, 1 << 50);
+        let schema = Schema::parse(
+            r#"
+            fields = [
+                { name = "name", type = "name" },
+                { name = "email", type = "email" },
+                { name = "text", type = "lorem", words = 3 },
+                { name = "score", type = "float", min = 0.0, max = 1.0, decimals = 2 },
+            ]
+            "#,
+        )
+        .unwrap();
+        let mut records = Records::new(&schema, 10).unwrap();
+
+        for _ in 0..8 {
+            let values = records.generate(&mut rng);
+            let text = |index: usize| match &values[index] {
+                Value::String(value) => value.clone(),
+                _ => panic!("expected string"),
+            };
+
+            assert_eq!(text(0).split(' ').count(), 2);
+            assert!(text(1).contains('@'));
+            assert_eq!(text(2).split(' ').count(), 3);
+            assert!(matches!(values[3], Value::Float(score) if (score * 100.0).fract() == 0.0));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let check = |source: &str| Records::new(&Schema::parse(source)?, 10).map(|_| ());
+
+        assert!(check("fields = []").is_err());
+        assert!(
+            check(r#"fields = [{ name = "a", type = "integer", min = 1, max = 2, mx = 3 }]"#)
+                .is_err()
+        );
+        assert!(check(r#"fields = [{ name = "a", type = "lorem", word = 3 }]"#).is_err());
+        assert!(check(r#"fields = [{ name = "a", type = "name", first = true }]"#).is_err());
+        assert!(check(
+            r#"fields = [{ name = "a", type = "bool" }, { name = "a", type = "name" }]"#
+        )
+        .is_err());
+        assert!(check(r#"fields = [{ name = "a", type = "unknown" }]"#).is_err());
+        assert!(check(r#"fields = [{ name = "a", type = "integer", min = 2, max = 1 }]"#).is_err());
+        assert!(check(r#"fields = [{ name = "a", type = "enum", values = [] }]"#).is_err());
+        assert!(check(
+            r#"fields = [{ name = "a", type = "enum", values = ["x"], weights = [1, 2] }]"#
+        )
+        .is_err());
+        assert!(check(r#"fields = [{ name = "a", type = "bool", probability = 2.0 }]"#).is_err());
+        assert!(check(r#"fields = [{ name = "a", type = "sequence", step = 0.5 }]"#).is_err());
+        assert!(
+            check(r#"fields = [{ name = "a", type = "date", after = "2000-13-01" }]"#).is_err()
+        );
+        let sequence =
+            r#"fields = [{ name = "a", type = "sequence", start = 9223372036854775800 }]"#;
+        assert!(check(sequence).is_err());
+        let mut records = Records::new(&Schema::parse(sequence).unwrap(), 8).unwrap();
+        let mut rng = StepRng::new(0, 1);
+        let last = (0..8).map(|_| records.generate(&mut rng)).last().unwrap();
+        assert!(matches!(last[0], Value::Integer(i64::MAX)));
+        for format in ["%Q", "%H:%M", "%#z"] {
+            let source = format!(
+                r#"fields = [{{ name = "a", type = "date", after = 2000-01-01, before = 2000-12-31, format = "{}" }}]"#,
+                format
+            );
+            assert!(check(&source).is_err(), "{}", format);
+        }
+    }
+}
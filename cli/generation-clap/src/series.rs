@@ -0,0 +1,228 @@
+use crate::error::Error;
+use chrono::{DateTime, TimeDelta, Utc};
+use rand::Rng;
+use rand_distr::{Bernoulli, Normal};
+use std::f64::consts::TAU;
+
+// Components are summed, every one of them can be switched off with 0
+#[derive(Clone, Debug, PartialEq)]
+pub struct Model {
+    pub level: f64,
+    // Change per step
+    pub trend: f64,
+    pub amplitude: f64,
+    // Steps per seasonal cycle
+    pub period: f64,
+    // Standard deviation of the random walk increments
+    pub walk: f64,
+    // Standard deviation of the independent noise
+    pub noise: f64,
+    // Probability of a spike per step
+    pub spikes: f64,
+    pub spike: f64,
+}
+
+pub struct Series {
+    model: Model,
+    walk: Normal<f64>,
+    noise: Normal<f64>,
+    spikes: Bernoulli,
+    time: DateTime<Utc>,
+    interval: TimeDelta,
+    step: u64,
+    drift: f64,
+}
+
+impl Series {
+    // `steps` bounds the series so every timestamp stays in range
+    pub fn new(
+        model: Model,
+        start: DateTime<Utc>,
+        interval: TimeDelta,
+        steps: u64,
+    ) -> Result<Self, Error> {
+        for (name, value) in [
+            ("level", model.level),
+            ("trend", model.trend),
+            ("amplitude", model.amplitude),
+            ("spike", model.spike),
+        ] {
+            if !value.is_finite() {
+                return Err(Error::parameter(format!(
+                    "{} must be a finite number",
+                    name
+                )));
+            }
+        }
+        if !model.period.is_finite() || model.period <= 0.0 {
+            return Err(Error::parameter("period must be longer than 0"));
+        }
+        if interval <= TimeDelta::zero() {
+            return Err(Error::parameter("interval must be longer than 0"));
+        }
+        i64::try_from(steps)
+            .ok()
+            .and_then(|steps| interval.num_milliseconds().checked_mul(steps))
+            .and_then(TimeDelta::try_milliseconds)
+            .and_then(|length| start.checked_add_signed(length))
+            .ok_or_else(|| Error::parameter("series runs past the supported time range"))?;
+        let deviation = |name: &str, value: f64| {
+            if !value.is_finite() || value < 0.0 {
+                return Err(Error::parameter(format!(
+                    "{} must be a finite number of at least 0",
+                    name
+                )));
+            }
+            Normal::new(0.0, value).map_err(|error| Error::parameter(error.to_string()))
+        };
+        let walk = deviation("walk", model.walk)?;
+        let noise = deviation("noise", model.noise)?;
+        let spikes = Bernoulli::new(model.spikes)
+            .map_err(|_| Error::parameter("spike probability must be between 0 and 1"))?;
+
+        Ok(Self {
+            model,
+            walk,
+            noise,
+            spikes,
+            time: start,
+            interval,
+            step: 0,
+            drift: 0.0,
+        })
+    }
+
+    pub fn next<R: Rng + ?Sized>(&mut self, rng: &mut R) -> (DateTime<Utc>, f64) {
+        let time = self.time;
+        let step = self.step as f64;
+        let model = &self.model;
+
+        let mut value = model.level
+            + model.trend * step
+            + model.amplitude * (TAU * step / model.period).sin()
+            + self.drift
+            + rng.sample(self.noise);
+        if rng.sample(self.spikes) {
+            value += model.spike * rng.gen_range(1.0..2.0);
+        }
+
+        self.drift += rng.sample(self.walk);
+        self.step += 1;
+        self.time = time + self.interval;
+
+        (time, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn model() -> Model {
+        Model {
+            level: 10.0,
+            trend: 0.0,
+            amplitude: 0.0,
+            period: 4.0,
+            walk: 0.0,
+            noise: 0.0,
+            spikes: 0.0,
+            spike: 0.0,
+        }
+    }
+
+    fn values(model: Model, count: usize) -> Vec<f64> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let mut series = Series::new(model, start, TimeDelta::minutes(1), count as u64).unwrap();
+        (0..count).map(|_| series.next(&mut rng).1).collect()
+    }
+
+    #[test]
+    fn timestamps() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let mut series = Series::new(model(), start, TimeDelta::minutes(5), 2).unwrap();
+
+        assert_eq!(series.next(&mut rng), (start, 10.0));
+        assert_eq!(series.next(&mut rng).0, start + TimeDelta::minutes(5));
+    }
+
+    #[test]
+    fn components() {
+        let trend = values(
+            Model {
+                trend: 0.5,
+                ..model()
+            },
+            3,
+        );
+        assert_eq!(trend, vec![10.0, 10.5, 11.0]);
+
+        let season = values(
+            Model {
+                amplitude: 2.0,
+                ..model()
+            },
+            4,
+        );
+        for (value, expected) in season.iter().zip([10.0, 12.0, 10.0, 8.0]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+
+        let spikes = values(
+            Model {
+                spikes: 1.0,
+                spike: 5.0,
+                ..model()
+            },
+            8,
+        );
+        assert!(spikes.iter().all(|value| (15.0..20.0).contains(value)));
+    }
+
+    #[test]
+    fn seeded() {
+        let noisy = Model {
+            walk: 1.0,
+            noise: 0.5,
+            spikes: 0.1,
+            spike: 20.0,
+            ..model()
+        };
+
+        assert_eq!(values(noisy.clone(), 32), values(noisy, 32));
+    }
+
+    #[test]
+    fn invalid() {
+        let start = Utc::now();
+        let interval = TimeDelta::minutes(1);
+
+        for model in [
+            Model {
+                period: 0.0,
+                ..model()
+            },
+            Model {
+                noise: -1.0,
+                ..model()
+            },
+            Model {
+                spikes: 1.5,
+                ..model()
+            },
+            Model {
+                level: f64::NAN,
+                ..model()
+            },
+        ] {
+            assert!(Series::new(model, start, interval, 1).is_err());
+        }
+        assert!(Series::new(model(), start, TimeDelta::zero(), 1).is_err());
+        assert!(Series::new(model(), start, TimeDelta::weeks(1), u64::MAX).is_err());
+    }
+}
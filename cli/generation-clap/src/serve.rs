@@ -0,0 +1,279 @@
+use crate::cli::{self, Cli, Commands};
+use crate::{configure, generate, parse};
+use clap::CommandFactory;
+use generation_clap::bytes::{self, Encoding};
+use generation_clap::config::Settings;
+use generation_clap::error::Error;
+use generation_clap::output::Format;
+use std::any::TypeId;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use tiny_http::{Header, Method, Response, Server};
+
+// Shorter paths for subcommands
+const ALIASES: [(&str, &str); 1] = [("range", "integer-range")];
+
+// Largest single value and whole response a request may produce, in bytes or characters
+const MAX_VALUE: u64 = 1 << 20;
+const MAX_BODY: usize = 64 << 20;
+
+// Status, content type and body of a response
+type Reply = (u16, &'static str, Vec<u8>);
+
+fn text(status: u16, message: impl Into<String>) -> Reply {
+    (
+        status,
+        "text/plain; charset=utf-8",
+        format!("{}\n", message.into()).into_bytes(),
+    )
+}
+
+// Commands that read or write files, or write to stdout directly, stay on the command line
+fn servable(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Serve { .. }
+            | Commands::Weighted { file: Some(_), .. }
+            | Commands::Test { .. }
+            | Commands::Image { .. }
+            | Commands::Maze { .. }
+            | Commands::Shuffle { .. }
+            | Commands::Sample { .. }
+            | Commands::Pick { .. }
+            | Commands::Records { .. }
+            | Commands::Roll { stats: true, .. }
+            | Commands::Passphrase { file: Some(_), .. }
+            | Commands::Bytes {
+                output: Some(_),
+                ..
+            }
+            | Commands::Bytes {
+                encoding: Encoding::Raw,
+                ..
+            }
+    )
+}
+
+// Size of one value a command asks for, checked before anything is allocated
+fn size(command: &Commands) -> Result<u64, Error> {
+    let size = match command {
+        Commands::String {
+            length: Some(length),
+        }
+        | Commands::Custom {
+            length: Some(length),
+            ..
+        }
+        | Commands::Password { length, .. } => *length,
+        Commands::Nanoid { size, .. } => *size,
+        Commands::Passphrase { words, digits, .. } => words.saturating_add(*digits),
+        Commands::Bytes { size, .. } => return bytes::size(size),
+        _ => 0,
+    };
+    Ok(size as u64)
+}
+
+// Response buffer that refuses to grow beyond `MAX_BODY`
+struct Body {
+    buffer: Vec<u8>,
+    full: bool,
+}
+
+impl Write for &mut Body {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buffer.len() + data.len() > MAX_BODY {
+            self.full = true;
+            return Err(io::Error::other("response is too large"));
+        }
+        self.buffer.write(data)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Command line for `/name?key=value`
+fn arguments(path: &str, query: &str) -> Result<Vec<String>, Reply> {
+    let name = path.trim_matches('/');
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, name)| name);
+    let cli = Cli::command();
+    let command = match cli.find_subcommand(name) {
+        Some(command) if !name.is_empty() => command,
+        _ => return Err(text(404, format!("unknown generator `{}`", name))),
+    };
+    let query = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect::<Vec<_>>();
+    // No parameter may name a file, whichever generator it belongs to
+    for (key, _) in &query {
+        let key = key.replace('_', "-");
+        let path = command
+            .get_arguments()
+            .chain(cli.get_arguments())
+            .any(|arg| {
+                (arg.get_long() == Some(key.as_str()) || arg.get_id() == key.as_str())
+                    && arg.get_value_parser().type_id() == TypeId::of::<PathBuf>()
+            });
+        if path {
+            return Err(text(
+                400,
+                format!("`{}` is only available on the command line", key),
+            ));
+        }
+    }
+    let mut arguments = vec![String::from("generation-clap")];
+    arguments.extend(cli::arguments(name, query).map_err(|error| text(400, error.to_string()))?);
+    Ok(arguments)
+}
+
+fn respond(method: &Method, url: &str, max_count: u64, settings: &Settings) -> Reply {
+    if *method != Method::Get {
+        return text(405, "only GET is supported");
+    }
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let arguments = match arguments(path, query) {
+        Ok(arguments) => arguments,
+        Err(reply) => return reply,
+    };
+    let cli = match parse(&arguments) {
+        Ok((cli, matches)) => configure(cli, &matches, settings),
+        Err(error) => return text(400, error.to_string().trim_end()),
+    };
+    if cli.profile.is_some() {
+        return text(400, "profiles are only available on the command line");
+    }
+    if cli.count > max_count {
+        return text(400, format!("count must not be above {}", max_count));
+    }
+    let command = match &cli.command {
+        Some(command) if servable(command) => command,
+        _ => return text(400, "this generator is only available on the command line"),
+    };
+    match size(command) {
+        Ok(size) if size > MAX_VALUE => {
+            return text(
+                400,
+                format!("values must not be larger than {} bytes", MAX_VALUE),
+            )
+        }
+        Ok(_) => {}
+        Err(error) => return text(400, error.to_string()),
+    }
+
+    let mut body = Body {
+        buffer: Vec::new(),
+        full: false,
+    };
+    let result = generate(&cli, &mut body).map(|_| ());
+    match result {
+        Ok(()) => {
+            let kind = match cli.format {
+                Format::Plain => "text/plain; charset=utf-8",
+                Format::Json => "application/json",
+                Format::Ndjson => "application/x-ndjson",
+                Format::Csv => "text/csv; charset=utf-8",
+            };
+            (200, kind, body.buffer)
+        }
+        Err(Error::Io(_)) if body.full => text(
+            400,
+            format!("responses must not be larger than {} bytes", MAX_BODY),
+        ),
+        Err(error @ Error::Io(_)) => text(500, error.to_string()),
+        Err(error) => text(400, error.to_string()),
+    }
+}
+
+pub fn serve(host: &str, port: u16, max_count: u64, settings: &Settings) -> Result<(), Error> {
+    let server = Server::http((host, port)).map_err(io::Error::other)?;
+    eprintln!("listening on http://{}:{}", host, port);
+
+    for request in server.incoming_requests() {
+        let (status, kind, body) = respond(request.method(), request.url(), max_count, settings);
+        let header = Header::from_bytes("Content-Type", kind).expect("content type is valid");
+        let response = Response::from_data(body)
+            .with_status_code(status)
+            .with_header(header);
+        if let Err(error) = request.respond(response) {
+            eprintln!("error: {}", error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get(url: &str) -> (u16, String) {
+        let (status, _, body) = respond(&Method::Get, url, 100, &Settings::new());
+        (status, String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn generators() {
+        let (status, body) = get("/range?start=1&end=10&count=5&seed=1");
+        assert_eq!(status, 200);
+        assert_eq!(body.lines().count(), 5);
+        assert!(body
+            .lines()
+            .all(|line| (1..10).contains(&line.parse::<i64>().unwrap())));
+        assert_eq!(get("/range?start=1&end=10&count=5&seed=1").1, body);
+
+        let (status, body) = get("/string?length=16&format=json&field=token&count=2");
+        let values: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(values[1]["token"].as_str().unwrap().len(), 16);
+
+        let (_, body) = get("/pattern?pattern=%5Ba-c%5D%7B3%7D&count=3");
+        assert!(body.lines().all(|line| line.len() == 3));
+        assert_eq!(
+            get("/password?no_ambiguous=true&length=24").1.trim().len(),
+            24
+        );
+        assert_eq!(get("/uuid").0, 200);
+    }
+
+    #[test]
+    fn rejected() {
+        assert_eq!(get("/nothing").0, 404);
+        assert_eq!(get("/").0, 404);
+        assert_eq!(get("/string?colour=red").0, 400);
+        assert_eq!(get("/string?length=abc").0, 400);
+        assert_eq!(get("/range?start=5&end=5").0, 400);
+        assert_eq!(get("/string?count=1000").0, 400);
+        assert_eq!(get("/shuffle").0, 400);
+        assert_eq!(get("/bytes?encoding=raw").0, 400);
+        assert_eq!(get("/bytes?size=64G").0, 400);
+        assert_eq!(get("/string?length=100000000").0, 400);
+        assert_eq!(get("/custom?length=100000000").0, 400);
+        assert_eq!(
+            get("/pattern?pattern=((a%7B1000%7D)%7B1000%7D)%7B1000%7D").0,
+            400
+        );
+        assert_eq!(get("/pattern?pattern=a*&max_repeat=100000000").0, 400);
+        for url in [
+            "/weighted?file=/etc/passwd",
+            "/passphrase?file=/etc/passwd",
+            "/records?schema=/etc/passwd",
+            "/shuffle?files=/etc/passwd",
+        ] {
+            let (status, body) = get(url);
+            assert_eq!(status, 400, "{}", url);
+            assert!(
+                body.contains("only available on the command line"),
+                "{}",
+                url
+            );
+            assert!(!body.contains("root"), "{}", url);
+        }
+        assert_eq!(get("/string?profile=keys").0, 400);
+        assert_eq!(
+            respond(&Method::Post, "/string", 100, &Settings::new()).0,
+            405
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use collections::vector::List;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// cargo bench --bench vector
+
+const SIZES: [u64; 3] = [100, 1_000, 10_000];
+
+// The previous vector::List, kept as a baseline: every operation scans the items
+struct Item {
+    id: u64,
+    active: bool,
+    name: String,
+    content: String,
+}
+
+struct Linear {
+    id: u64,
+    items: Vec<Item>,
+}
+
+impl Linear {
+    fn new() -> Self {
+        Self {
+            id: 0,
+            items: vec![],
+        }
+    }
+    fn one(&self, id: u64) -> Option<&Item> {
+        self.items.iter().find(|item| item.id == id)
+    }
+    fn add(&mut self, name: &str, content: &str, active: bool) -> u64 {
+        self.id += 1;
+        self.items.push(Item {
+            id: self.id,
+            active,
+            name: name.into(),
+            content: content.into(),
+        });
+        self.id
+    }
+    fn update(&mut self, id: u64, name: &str, content: &str, active: bool) {
+        for item in self.items.iter_mut() {
+            if item.id == id {
+                item.active = active;
+                item.name = name.into();
+                item.content = content.into();
+            }
+        }
+    }
+    fn remove(&mut self, id: u64) {
+        self.items.retain(|item| item.id != id)
+    }
+}
+
+fn filled(size: u64) -> (List, Vec<u64>, Linear, Vec<u64>) {
+    let mut list = List::new();
+    let mut linear = Linear::new();
+    let ids = (0..size)
+        .map(|_| list.add("name", "content", true))
+        .collect();
+    let linear_ids = (0..size)
+        .map(|_| linear.add("name", "content", true))
+        .collect();
+    (list, ids, linear, linear_ids)
+}
+
+fn one(c: &mut Criterion) {
+    let mut group = c.benchmark_group("one");
+    for size in SIZES {
+        let (list, ids, linear, linear_ids) = filled(size);
+        group.bench_with_input(BenchmarkId::new("slot map", size), &size, |b, _| {
+            b.iter(|| {
+                ids.iter()
+                    .step_by(10)
+                    .filter_map(|id| list.one(*id))
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("linear", size), &size, |b, _| {
+            b.iter(|| {
+                linear_ids
+                    .iter()
+                    .step_by(10)
+                    .filter_map(|id| linear.one(*id))
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    for size in SIZES {
+        let (mut list, ids, mut linear, linear_ids) = filled(size);
+        let middle = size as usize / 2;
+        group.bench_with_input(BenchmarkId::new("slot map", size), &size, |b, _| {
+            b.iter(|| list.update(black_box(ids[middle]), "name", "content", false))
+        });
+        group.bench_with_input(BenchmarkId::new("linear", size), &size, |b, _| {
+            b.iter(|| linear.update(black_box(linear_ids[middle]), "name", "content", false))
+        });
+    }
+    group.finish();
+}
+
+fn remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove");
+    for size in SIZES {
+        let (mut list, ids, mut linear, linear_ids) = filled(size);
+        let middle = size as usize / 2;
+        let mut id = ids[middle];
+        group.bench_with_input(BenchmarkId::new("slot map", size), &size, |b, _| {
+            b.iter(|| {
+                list.remove(black_box(id));
+                id = list.add("name", "content", true);
+            })
+        });
+        let mut id = linear_ids[middle];
+        group.bench_with_input(BenchmarkId::new("linear", size), &size, |b, _| {
+            b.iter(|| {
+                linear.remove(black_box(id));
+                id = linear.add("name", "content", true);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for size in SIZES {
+        let (list, _, linear, _) = filled(size);
+        group.bench_with_input(BenchmarkId::new("slot map", size), &size, |b, _| {
+            b.iter(|| {
+                list.all()
+                    .iter()
+                    .filter(|item| item.active() && item.name() != item.content())
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("linear", size), &size, |b, _| {
+            b.iter(|| {
+                linear
+                    .items
+                    .iter()
+                    .filter(|item| item.active && item.name != item.content)
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, one, update, remove, iterate);
+criterion_main!(benches);
@@ -1,3 +1,4 @@
+use crate::query::{Query, Record, Type, Value};
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -28,6 +29,26 @@ impl Item {
     }
 }
 
+impl Record for Item {
+    fn schema() -> &'static [(&'static str, Type)] {
+        &[
+            ("id", Type::Integer),
+            ("active", Type::Bool),
+            ("name", Type::String),
+            ("content", Type::String),
+        ]
+    }
+    fn field(&self, name: &str) -> Option<Value<'_>> {
+        match name {
+            "id" => Some(Value::Integer(self.id.into())),
+            "active" => Some(Value::Bool(self.active)),
+            "name" => Some(Value::String(&self.name)),
+            "content" => Some(Value::String(&self.content)),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct List {
     id: u32,
@@ -65,6 +86,15 @@ impl List {
     pub fn remove(&mut self, id: u32) -> Option<Item> {
         self.items.remove(&id)
     }
+    pub fn filter(&self, query: &Query<Item>) -> Vec<&Item> {
+        query.filter(self.all())
+    }
+}
+
+impl Default for List {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
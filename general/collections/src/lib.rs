@@ -0,0 +1,5 @@
+pub mod hash;
+pub mod query;
+pub mod storage;
+pub mod tree;
+pub mod vector;
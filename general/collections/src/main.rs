@@ -1,20 +1,37 @@
-mod hash;
-mod tree;
-mod vector;
-
-use hash::List as HList;
-use tree::List as TList;
-use vector::List as VList;
+use collections::hash::{Item as HItem, List as HList};
+use collections::query::{Query, Record};
+use collections::storage::{self, Secret};
+use collections::tree::{Item as TItem, List as TList};
+use collections::vector::{Item as VItem, List as VList};
+use std::env;
+
+// cargo run -- 'active = true and (name ~ "name" or content contains "03")'
+
+fn filter<T: Record, F>(name: &str, source: &str, filter: F)
+where
+    F: Fn(&Query<T>) -> usize,
+{
+    match Query::<T>::new(source) {
+        Ok(query) => print!("{} Query Matches: {}\n\n", name, filter(&query)),
+        Err(error) => print!(
+            "{} Query Error ({:?}): {}\n\n{}\n\n",
+            name,
+            error.span(),
+            error.message(),
+            error.render(source)
+        ),
+    }
+}
 
 fn main() {
     let mut vlist = VList::new();
 
     vlist.add("Name01", "Content01", true);
-    vlist.add("Name02", "Content02", false);
-    vlist.add("Name", "Content", false);
+    let id2 = vlist.add("Name02", "Content02", false);
+    let id3 = vlist.add("Name", "Content", false);
 
-    vlist.update(3, "Name03", "Content03", true);
-    vlist.remove(2);
+    vlist.update(id3, "Name03", "Content03", true);
+    vlist.remove(id2);
 
     print!("\n{}\n\n", "Vector Collection:");
 
@@ -22,7 +39,7 @@ fn main() {
         "{:#?}\n\n{:#?}\n\n{:#?}\n\n",
         vlist,
         vlist.all(),
-        vlist.one(3)
+        vlist.one(id3)
     );
 
     let mut hlist = HList::new();
@@ -77,4 +94,41 @@ fn main() {
         tlist.all(),
         tlist.one(item3.id())
     );
+
+    let path = env::temp_dir().join("collections.bin");
+    let key = [42; storage::KEY_LENGTH];
+
+    storage::save(&tlist, &path, Secret::Passphrase("passphrase")).expect("Saving list failed");
+    storage::rotate(&path, Secret::Passphrase("passphrase"), Secret::Key(&key))
+        .expect("Rotating key failed");
+
+    print!("Encrypted BTreeMap Collection:\n\n");
+
+    print!(
+        "{:#?}\n\n{}\n\n",
+        storage::load(&path, Secret::Key(&key)).expect("Loading list failed") == tlist,
+        storage::load(&path, Secret::Passphrase("passphrase")).unwrap_err()
+    );
+
+    let source = env::args()
+        .nth(1)
+        .unwrap_or_else(|| r#"active = true and (name ~ "name" or content contains "03")"#.into());
+
+    print!("Query: {}\n\n", source);
+
+    filter::<VItem, _>("Vector", &source, |query| {
+        let items = vlist.filter(query);
+        print!("{:#?}\n\n", items);
+        items.len()
+    });
+    filter::<HItem, _>("HashMap", &source, |query| {
+        let items = hlist.filter(query);
+        print!("{:#?}\n\n", items);
+        items.len()
+    });
+    filter::<TItem, _>("BTreeMap", &source, |query| {
+        let items = tlist.filter(query);
+        print!("{:#?}\n\n", items);
+        items.len()
+    });
 }
@@ -0,0 +1,682 @@
+use chrono::prelude::*;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+pub type Span = Range<usize>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Type {
+    Integer,
+    Bool,
+    String,
+    DateTime,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Integer => write!(f, "integer"),
+            Type::Bool => write!(f, "bool"),
+            Type::String => write!(f, "string"),
+            Type::DateTime => write!(f, "datetime"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    Integer(i64),
+    Bool(bool),
+    String(&'a str),
+    DateTime(DateTime<Utc>),
+}
+
+// Implemented by every collection item that can be filtered with a query
+pub trait Record {
+    fn schema() -> &'static [(&'static str, Type)];
+    fn field(&self, name: &str) -> Option<Value<'_>>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    message: String,
+    span: Span,
+}
+
+impl Error {
+    fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    pub fn span(&self) -> Span {
+        self.span.clone()
+    }
+    pub fn render(&self, source: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let end = self.span.end.clamp(start, source.len());
+        format!(
+            "{}\n{}{}\n{}",
+            source,
+            " ".repeat(source[..start].chars().count()),
+            "^".repeat(source[start..end].chars().count().max(1)),
+            self
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Integer(i64),
+    True,
+    False,
+    And,
+    Or,
+    Not,
+    Contains,
+    LeftParen,
+    RightParen,
+    Operator(Operator),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Identifier(name) => write!(f, "`{}`", name),
+            Token::String(value) => write!(f, "{:?}", value),
+            Token::Integer(value) => write!(f, "`{}`", value),
+            Token::True => write!(f, "`true`"),
+            Token::False => write!(f, "`false`"),
+            Token::And => write!(f, "`and`"),
+            Token::Or => write!(f, "`or`"),
+            Token::Not => write!(f, "`not`"),
+            Token::Contains => write!(f, "`contains`"),
+            Token::LeftParen => write!(f, "`(`"),
+            Token::RightParen => write!(f, "`)`"),
+            Token::Operator(operator) => write!(f, "`{}`", operator),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Like,
+    Contains,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::Like => "~",
+            Operator::Contains => "contains",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl Operator {
+    fn accepts(&self, kind: Type) -> bool {
+        match self {
+            Operator::Equal | Operator::NotEqual => true,
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => {
+                kind != Type::Bool
+            }
+            Operator::Like | Operator::Contains => kind == Type::String,
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Span)>, Error> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let token = match c {
+            '(' => {
+                chars.next();
+                Token::LeftParen
+            }
+            ')' => {
+                chars.next();
+                Token::RightParen
+            }
+            '=' => {
+                chars.next();
+                Token::Operator(Operator::Equal)
+            }
+            '~' => {
+                chars.next();
+                Token::Operator(Operator::Like)
+            }
+            '!' | '<' | '>' => {
+                chars.next();
+                let equal = chars.next_if(|&(_, c)| c == '=').is_some();
+                match (c, equal) {
+                    ('!', true) => Token::Operator(Operator::NotEqual),
+                    ('<', true) => Token::Operator(Operator::LessEqual),
+                    ('<', false) => Token::Operator(Operator::Less),
+                    ('>', true) => Token::Operator(Operator::GreaterEqual),
+                    ('>', false) => Token::Operator(Operator::Greater),
+                    _ => return Err(Error::new("expected `!=`", start..start + 1)),
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((index, '\\')) => match chars.next() {
+                            Some((_, '"')) => value.push('"'),
+                            Some((_, '\\')) => value.push('\\'),
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((end, c)) => {
+                                return Err(Error::new(
+                                    format!("unknown escape `\\{}`", c),
+                                    index..end + c.len_utf8(),
+                                ))
+                            }
+                            None => {
+                                return Err(Error::new("unterminated string", start..source.len()))
+                            }
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(Error::new("unterminated string", start..source.len())),
+                    }
+                }
+                Token::String(value)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                chars.next();
+                while chars.next_if(|&(_, c)| c.is_ascii_digit()).is_some() {}
+                let end = chars.peek().map_or(source.len(), |&(index, _)| index);
+                let value = source[start..end]
+                    .parse()
+                    .map_err(|_| Error::new("invalid integer", start..end))?;
+                Token::Integer(value)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while chars
+                    .next_if(|&(_, c)| c.is_alphanumeric() || c == '_')
+                    .is_some()
+                {}
+                let end = chars.peek().map_or(source.len(), |&(index, _)| index);
+                match &source[start..end] {
+                    "true" => Token::True,
+                    "false" => Token::False,
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "contains" => Token::Contains,
+                    name => Token::Identifier(name.into()),
+                }
+            }
+            c => {
+                return Err(Error::new(
+                    format!("unexpected character `{}`", c),
+                    start..start + c.len_utf8(),
+                ))
+            }
+        };
+
+        let end = chars.peek().map_or(source.len(), |&(index, _)| index);
+        tokens.push((token, start..end));
+    }
+
+    tokens.push((Token::End, source.len()..source.len()));
+
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Literal {
+    Integer(i64),
+    Bool(bool),
+    String(String),
+    DateTime(DateTime<Utc>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Or(Box<Expression>, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Field(String, Span),
+    Compare(String, Span, Operator, Span, Literal, Span),
+}
+
+struct Parser {
+    tokens: Vec<(Token, Span)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+    fn next(&mut self) -> (Token, Span) {
+        let token = self.tokens[self.position].clone();
+        if token.0 != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+    fn parse(&mut self) -> Result<Expression, Error> {
+        let expression = self.or()?;
+        match self.next() {
+            (Token::End, _) => Ok(expression),
+            (token, span) => Err(Error::new(format!("unexpected {}", token), span)),
+        }
+    }
+    fn or(&mut self) -> Result<Expression, Error> {
+        let mut left = self.and()?;
+        while *self.peek() == Token::Or {
+            self.next();
+            left = Expression::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+    fn and(&mut self) -> Result<Expression, Error> {
+        let mut left = self.not()?;
+        while *self.peek() == Token::And {
+            self.next();
+            left = Expression::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+    fn not(&mut self) -> Result<Expression, Error> {
+        if *self.peek() == Token::Not {
+            self.next();
+            return Ok(Expression::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+    fn primary(&mut self) -> Result<Expression, Error> {
+        match self.next() {
+            (Token::LeftParen, open) => {
+                let expression = self.or()?;
+                match self.next() {
+                    (Token::RightParen, _) => Ok(expression),
+                    (token, span) => Err(Error::new(
+                        format!(
+                            "expected `)` to close `(` at {}, found {}",
+                            open.start, token
+                        ),
+                        span,
+                    )),
+                }
+            }
+            (Token::Identifier(name), field) => {
+                let operator = match self.peek() {
+                    Token::Operator(operator) => *operator,
+                    Token::Contains => Operator::Contains,
+                    _ => return Ok(Expression::Field(name, field)),
+                };
+                let (_, operator_span) = self.next();
+                let (literal, literal_span) = match self.next() {
+                    (Token::Integer(value), span) => (Literal::Integer(value), span),
+                    (Token::String(value), span) => (Literal::String(value), span),
+                    (Token::True, span) => (Literal::Bool(true), span),
+                    (Token::False, span) => (Literal::Bool(false), span),
+                    (token, span) => {
+                        return Err(Error::new(
+                            format!("expected a value after `{}`, found {}", operator, token),
+                            span,
+                        ))
+                    }
+                };
+                Ok(Expression::Compare(
+                    name,
+                    field,
+                    operator,
+                    operator_span,
+                    literal,
+                    literal_span,
+                ))
+            }
+            (token, span) => Err(Error::new(
+                format!("expected a field or `(`, found {}", token),
+                span,
+            )),
+        }
+    }
+}
+
+fn check(expression: Expression, schema: &[(&str, Type)]) -> Result<Expression, Error> {
+    let kind = |name: &str, span: &Span| {
+        schema
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, kind)| *kind)
+            .ok_or_else(|| Error::new(format!("unknown field `{}`", name), span.clone()))
+    };
+
+    match expression {
+        Expression::Or(left, right) => Ok(Expression::Or(
+            Box::new(check(*left, schema)?),
+            Box::new(check(*right, schema)?),
+        )),
+        Expression::And(left, right) => Ok(Expression::And(
+            Box::new(check(*left, schema)?),
+            Box::new(check(*right, schema)?),
+        )),
+        Expression::Not(inner) => Ok(Expression::Not(Box::new(check(*inner, schema)?))),
+        Expression::Field(name, span) => match kind(&name, &span)? {
+            Type::Bool => Ok(Expression::Field(name, span)),
+            other => Err(Error::new(
+                format!("field `{}` is {}, expected a comparison", name, other),
+                span,
+            )),
+        },
+        Expression::Compare(name, field, operator, operator_span, literal, literal_span) => {
+            let field_type = kind(&name, &field)?;
+            if !operator.accepts(field_type) {
+                return Err(Error::new(
+                    format!(
+                        "operator `{}` cannot be applied to {}",
+                        operator, field_type
+                    ),
+                    operator_span,
+                ));
+            }
+            let literal = match (field_type, literal) {
+                (Type::Integer, literal @ Literal::Integer(_)) => literal,
+                (Type::Bool, literal @ Literal::Bool(_)) => literal,
+                (Type::String, literal @ Literal::String(_)) => literal,
+                (Type::DateTime, Literal::String(value)) => DateTime::parse_from_rfc3339(&value)
+                    .map(|datetime| Literal::DateTime(datetime.with_timezone(&Utc)))
+                    .map_err(|_| {
+                        Error::new(
+                            format!("invalid RFC 3339 datetime for field `{}`", name),
+                            literal_span.clone(),
+                        )
+                    })?,
+                (expected, _) => {
+                    return Err(Error::new(
+                        format!(
+                            "field `{}` is {}, value has a different type",
+                            name, expected
+                        ),
+                        literal_span,
+                    ))
+                }
+            };
+            Ok(Expression::Compare(
+                name,
+                field,
+                operator,
+                operator_span,
+                literal,
+                literal_span,
+            ))
+        }
+    }
+}
+
+fn compare<T: PartialOrd>(left: T, operator: Operator, right: T) -> bool {
+    match operator {
+        Operator::Equal => left == right,
+        Operator::NotEqual => left != right,
+        Operator::Less => left < right,
+        Operator::LessEqual => left <= right,
+        Operator::Greater => left > right,
+        Operator::GreaterEqual => left >= right,
+        Operator::Like | Operator::Contains => false,
+    }
+}
+
+fn evaluate<T: Record>(expression: &Expression, record: &T) -> bool {
+    match expression {
+        Expression::Or(left, right) => evaluate(left, record) || evaluate(right, record),
+        Expression::And(left, right) => evaluate(left, record) && evaluate(right, record),
+        Expression::Not(inner) => !evaluate(inner, record),
+        Expression::Field(name, _) => matches!(record.field(name), Some(Value::Bool(true))),
+        Expression::Compare(name, _, operator, _, literal, _) => {
+            match (record.field(name), literal) {
+                (Some(Value::Integer(left)), Literal::Integer(right)) => {
+                    compare(left, *operator, *right)
+                }
+                (Some(Value::Bool(left)), Literal::Bool(right)) => compare(left, *operator, *right),
+                (Some(Value::DateTime(left)), Literal::DateTime(right)) => {
+                    compare(left, *operator, *right)
+                }
+                (Some(Value::String(left)), Literal::String(right)) => match operator {
+                    Operator::Contains => left.contains(right.as_str()),
+                    Operator::Like => left.to_lowercase().contains(&right.to_lowercase()),
+                    _ => compare(left, *operator, right.as_str()),
+                },
+                _ => false,
+            }
+        }
+    }
+}
+
+// A parsed and type checked filter over the fields of `T`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query<T: Record> {
+    expression: Expression,
+    record: PhantomData<T>,
+}
+
+impl<T: Record> Query<T> {
+    pub fn new(source: &str) -> Result<Self, Error> {
+        let tokens = tokenize(source)?;
+        let expression = Parser {
+            tokens,
+            position: 0,
+        }
+        .parse()?;
+        Ok(Self {
+            expression: check(expression, T::schema())?,
+            record: PhantomData,
+        })
+    }
+    pub fn matches(&self, record: &T) -> bool {
+        evaluate(&self.expression, record)
+    }
+    pub fn filter<'a, I>(&self, records: I) -> Vec<&'a T>
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        records
+            .into_iter()
+            .filter(|record| self.matches(record))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hash::{Item as HItem, List as HList};
+    use crate::tree::{Item as TItem, List as TList};
+    use crate::vector::{Item as VItem, List as VList};
+
+    fn vlist() -> VList {
+        let mut list = VList::new();
+        list.add("foo", "first", true);
+        list.add("Foobar", "second bar", false);
+        list.add("baz", "third bar", true);
+        list
+    }
+
+    #[test]
+    fn query_vector() {
+        let list = vlist();
+        let query =
+            Query::<VItem>::new(r#"active = true and (name ~ "foo" or content contains "bar")"#)
+                .unwrap();
+
+        let ids = list
+            .filter(&query)
+            .iter()
+            .map(|item| item.field("id"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, vec![Some(Value::Integer(0)), Some(Value::Integer(2))]);
+    }
+
+    #[test]
+    fn query_hash() {
+        let mut list = HList::new();
+        list.add("foo", "first", true);
+        list.add("bar", "second", false);
+        list.add("baz", "third", true);
+
+        let query = Query::<HItem>::new("not active or id >= 3").unwrap();
+
+        let ids = list
+            .filter(&query)
+            .iter()
+            .map(|item| item.field("id"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, vec![Some(Value::Integer(2)), Some(Value::Integer(3))]);
+    }
+
+    #[test]
+    fn query_tree() {
+        let mut list = TList::new();
+        list.add("foo", "first", true);
+        list.add("bar", "second", false);
+
+        let query =
+            Query::<TItem>::new(r#"name != "foo" and created >= "1234-05-06T07:08:09Z""#).unwrap();
+
+        let names = list
+            .filter(&query)
+            .iter()
+            .map(|item| item.name())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["bar"]);
+    }
+
+    #[test]
+    fn query_precedence() {
+        let list = vlist();
+        let query = Query::<VItem>::new(r#"not active and name = "Foobar" or id = 0"#).unwrap();
+
+        assert_eq!(list.filter(&query).len(), 2);
+    }
+
+    #[test]
+    fn query_escape() {
+        let mut list = VList::new();
+        list.add("say \"hi\"", "", true);
+
+        let query = Query::<VItem>::new(r#"name = "say \"hi\"""#).unwrap();
+
+        assert_eq!(list.filter(&query).len(), 1);
+    }
+
+    #[test]
+    fn error_unknown_field() {
+        let error = Query::<VItem>::new(r#"active and nme = "x""#).unwrap_err();
+
+        assert_eq!(error.span(), 11..14);
+        assert_eq!(error.message(), "unknown field `nme`");
+    }
+
+    #[test]
+    fn error_operator_type() {
+        let error = Query::<VItem>::new("active < true").unwrap_err();
+
+        assert_eq!(error.span(), 7..8);
+    }
+
+    #[test]
+    fn error_literal_type() {
+        let error = Query::<VItem>::new(r#"id = "1""#).unwrap_err();
+
+        assert_eq!(error.span(), 5..8);
+    }
+
+    #[test]
+    fn error_datetime() {
+        let error = Query::<TItem>::new(r#"updated < "yesterday""#).unwrap_err();
+
+        assert_eq!(error.span(), 10..21);
+    }
+
+    #[test]
+    fn error_bare_field() {
+        let error = Query::<VItem>::new("name").unwrap_err();
+
+        assert_eq!(error.span(), 0..4);
+    }
+
+    #[test]
+    fn error_syntax() {
+        assert_eq!(Query::<VItem>::new("(active").unwrap_err().span(), 7..7);
+        assert_eq!(Query::<VItem>::new("active )").unwrap_err().span(), 7..8);
+        assert_eq!(
+            Query::<VItem>::new(r#"name = "open"#).unwrap_err().span(),
+            7..12
+        );
+        assert_eq!(Query::<VItem>::new("id = 1 $").unwrap_err().span(), 7..8);
+        assert_eq!(Query::<VItem>::new("id =").unwrap_err().span(), 4..4);
+    }
+
+    #[test]
+    fn error_render() {
+        let error = Query::<VItem>::new("id = true").unwrap_err();
+
+        assert_eq!(
+            error.render("id = true"),
+            "id = true\n     ^^^^\nfield `id` is integer, value has a different type at 5..9"
+        );
+
+        let source = r#"id = "éé""#;
+        let error = Query::<VItem>::new(source).unwrap_err();
+
+        assert_eq!(error.render(source).lines().nth(1), Some("     ^^^^"));
+        assert_eq!(
+            Query::<VItem>::new("id =")
+                .unwrap_err()
+                .render("id =")
+                .lines()
+                .nth(1),
+            Some("    ^")
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::tree::List;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::ffi::OsString;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+pub const KEY_LENGTH: usize = 32;
+
+const MAGIC: &[u8; 4] = b"CLST";
+const VERSION: u8 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const HEADER_LENGTH: usize = MAGIC.len() + 2 + SALT_LENGTH + NONCE_LENGTH;
+
+const RAW: u8 = 0;
+const ARGON2: u8 = 1;
+
+// Either a passphrase stretched with Argon2id or a raw 256 bit key
+#[derive(Clone, Copy)]
+pub enum Secret<'a> {
+    Passphrase(&'a str),
+    Key(&'a [u8; KEY_LENGTH]),
+}
+
+impl Secret<'_> {
+    fn kind(&self) -> u8 {
+        match self {
+            Secret::Passphrase(_) => ARGON2,
+            Secret::Key(_) => RAW,
+        }
+    }
+    fn key(&self, salt: &[u8]) -> Result<[u8; KEY_LENGTH], Error> {
+        match self {
+            Secret::Passphrase(passphrase) => {
+                let mut key = [0; KEY_LENGTH];
+                Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(Error::KeyDerivation)?;
+                Ok(key)
+            }
+            Secret::Key(key) => Ok(**key),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Format(&'static str),
+    Decryption,
+    Serialization(serde_json::Error),
+    KeyDerivation(argon2::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "io error: {}", error),
+            Error::Format(reason) => write!(f, "invalid file: {}", reason),
+            Error::Decryption => write!(f, "decryption failed: wrong key or tampered file"),
+            Error::Serialization(error) => write!(f, "serialization error: {}", error),
+            Error::KeyDerivation(error) => write!(f, "key derivation error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Serialization(error)
+    }
+}
+
+// Layout: magic | version | kdf | salt | nonce | ciphertext, the header is authenticated
+pub fn encrypt(list: &List, secret: Secret) -> Result<Vec<u8>, Error> {
+    let mut salt = [0; SALT_LENGTH];
+    if let Secret::Passphrase(_) = secret {
+        OsRng.fill_bytes(&mut salt);
+    }
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.push(secret.kind());
+    bytes.extend_from_slice(&salt);
+    bytes.extend_from_slice(&nonce);
+
+    let key = secret.key(&salt)?;
+    let plaintext = serde_json::to_vec(list)?;
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &bytes,
+            },
+        )
+        .map_err(|_| Error::Decryption)?;
+
+    bytes.extend_from_slice(&ciphertext);
+
+    Ok(bytes)
+}
+
+pub fn decrypt(bytes: &[u8], secret: Secret) -> Result<List, Error> {
+    if bytes.len() < HEADER_LENGTH {
+        return Err(Error::Format("truncated header"));
+    }
+    let (header, ciphertext) = bytes.split_at(HEADER_LENGTH);
+    if &header[..MAGIC.len()] != MAGIC {
+        return Err(Error::Format("unknown magic"));
+    }
+    if header[MAGIC.len()] != VERSION {
+        return Err(Error::Format("unsupported version"));
+    }
+    let kind = header[MAGIC.len() + 1];
+    if kind != RAW && kind != ARGON2 {
+        return Err(Error::Format("unknown key derivation"));
+    }
+    if kind != secret.kind() {
+        return Err(Error::Decryption);
+    }
+
+    let salt = &header[MAGIC.len() + 2..MAGIC.len() + 2 + SALT_LENGTH];
+    let nonce = XNonce::from_slice(&header[HEADER_LENGTH - NONCE_LENGTH..]);
+
+    let key = secret.key(salt)?;
+    let plaintext = XChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| Error::Decryption)?;
+
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+pub fn save(list: &List, path: impl AsRef<Path>, secret: Secret) -> Result<(), Error> {
+    let path = path.as_ref();
+    let mut temporary = OsString::from(path.as_os_str());
+    temporary.push(".tmp");
+
+    fs::write(&temporary, encrypt(list, secret)?)?;
+    fs::rename(&temporary, path)?;
+
+    Ok(())
+}
+
+pub fn load(path: impl AsRef<Path>, secret: Secret) -> Result<List, Error> {
+    decrypt(&fs::read(path)?, secret)
+}
+
+pub fn rotate(path: impl AsRef<Path>, old: Secret, new: Secret) -> Result<(), Error> {
+    let list = load(&path, old)?;
+    save(&list, &path, new)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+
+    const KEY: [u8; KEY_LENGTH] = [7; KEY_LENGTH];
+
+    fn path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("collections-{}-{}.bin", std::process::id(), name))
+    }
+
+    fn list() -> List {
+        let mut list = List::new();
+        list.add("name1", "content1", true);
+        list.add("name2", "content2", false);
+        list
+    }
+
+    #[test]
+    fn roundtrip_key() {
+        let list = list();
+        let bytes = encrypt(&list, Secret::Key(&KEY)).unwrap();
+
+        assert_eq!(decrypt(&bytes, Secret::Key(&KEY)).unwrap(), list);
+    }
+
+    #[test]
+    fn roundtrip_passphrase() {
+        let list = list();
+        let path = path("passphrase");
+
+        save(&list, &path, Secret::Passphrase("secret")).unwrap();
+        let loaded = load(&path, Secret::Passphrase("secret"));
+        let wrong = load(&path, Secret::Passphrase("wrong"));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), list);
+        assert!(matches!(wrong, Err(Error::Decryption)));
+    }
+
+    #[test]
+    fn wrong_key() {
+        let bytes = encrypt(&list(), Secret::Key(&KEY)).unwrap();
+
+        assert!(matches!(
+            decrypt(&bytes, Secret::Key(&[8; KEY_LENGTH])),
+            Err(Error::Decryption)
+        ));
+    }
+
+    #[test]
+    fn tampered() {
+        let mut bytes = encrypt(&list(), Secret::Key(&KEY)).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+
+        assert!(matches!(
+            decrypt(&bytes, Secret::Key(&KEY)),
+            Err(Error::Decryption)
+        ));
+
+        let mut bytes = encrypt(&list(), Secret::Key(&KEY)).unwrap();
+        bytes[MAGIC.len() + 2] ^= 1;
+
+        assert!(matches!(
+            decrypt(&bytes, Secret::Key(&KEY)),
+            Err(Error::Decryption)
+        ));
+    }
+
+    #[test]
+    fn invalid_format() {
+        let bytes = encrypt(&list(), Secret::Key(&KEY)).unwrap();
+
+        assert!(matches!(
+            decrypt(&bytes[..10], Secret::Key(&KEY)),
+            Err(Error::Format(_))
+        ));
+        assert!(matches!(
+            decrypt(&[0; HEADER_LENGTH + 16], Secret::Key(&KEY)),
+            Err(Error::Format(_))
+        ));
+    }
+
+    #[test]
+    fn rotation() {
+        let list = list();
+        let path = path("rotation");
+        let new = [9; KEY_LENGTH];
+
+        save(&list, &path, Secret::Key(&KEY)).unwrap();
+        rotate(&path, Secret::Key(&KEY), Secret::Key(&new)).unwrap();
+        let old = load(&path, Secret::Key(&KEY));
+        let loaded = load(&path, Secret::Key(&new));
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(old, Err(Error::Decryption)));
+        assert_eq!(loaded.unwrap(), list);
+    }
+}
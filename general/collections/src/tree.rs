@@ -1,6 +1,8 @@
+use crate::query::{Query, Record, Type, Value};
 use chrono::prelude::*;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::default::Default;
 
@@ -24,7 +26,7 @@ fn random(length: usize) -> String {
         .collect::<String>()
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Item {
     id: String,
     active: bool,
@@ -67,7 +69,31 @@ impl Item {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl Record for Item {
+    fn schema() -> &'static [(&'static str, Type)] {
+        &[
+            ("id", Type::String),
+            ("active", Type::Bool),
+            ("name", Type::String),
+            ("content", Type::String),
+            ("created", Type::DateTime),
+            ("updated", Type::DateTime),
+        ]
+    }
+    fn field(&self, name: &str) -> Option<Value<'_>> {
+        match name {
+            "id" => Some(Value::String(&self.id)),
+            "active" => Some(Value::Bool(self.active)),
+            "name" => Some(Value::String(&self.name)),
+            "content" => Some(Value::String(&self.content)),
+            "created" => Some(Value::DateTime(self.created)),
+            "updated" => Some(Value::DateTime(self.updated)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct List {
     items: BTreeMap<String, Item>,
 }
@@ -100,6 +126,9 @@ impl List {
     pub fn remove(&mut self, id: &str) -> Option<Item> {
         self.items.remove(id)
     }
+    pub fn filter(&self, query: &Query<Item>) -> Vec<&Item> {
+        query.filter(self.items.values())
+    }
 }
 
 impl Default for List {
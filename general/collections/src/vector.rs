@@ -1,13 +1,31 @@
+use crate::query::{Query, Record, Type, Value};
+
+// Ids hold the slot in the low and the generation in the high 32 bits,
+// generations stop below 2^31 so ids always fit in an i64
+const GENERATIONS: u32 = 1 << 31;
+
+fn id(slot: u32, generation: u32) -> u64 {
+    (generation as u64) << 32 | slot as u64
+}
+
+fn slot(id: u64) -> usize {
+    (id & u32::MAX as u64) as usize
+}
+
+fn generation(id: u64) -> u32 {
+    (id >> 32) as u32
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Item {
-    id: u32,
+    id: u64,
     active: bool,
     name: String,
     content: String,
 }
 
 impl Item {
-    fn new(id: u32, name: &str, content: &str, active: bool) -> Self {
+    fn new(id: u64, name: &str, content: &str, active: bool) -> Self {
         Item {
             id,
             active,
@@ -21,40 +39,118 @@ impl Item {
         self.content = content.into();
         self
     }
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+    pub fn active(&self) -> bool {
+        self.active
+    }
+}
+
+impl Record for Item {
+    fn schema() -> &'static [(&'static str, Type)] {
+        &[
+            ("id", Type::Integer),
+            ("active", Type::Bool),
+            ("name", Type::String),
+            ("content", Type::String),
+        ]
+    }
+    fn field(&self, name: &str) -> Option<Value<'_>> {
+        match name {
+            "id" => Some(Value::Integer(self.id as i64)),
+            "active" => Some(Value::Bool(self.active)),
+            "name" => Some(Value::String(&self.name)),
+            "content" => Some(Value::String(&self.content)),
+            _ => None,
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Slot {
+    generation: u32,
+    index: Option<usize>,
+}
+
+// Generational slot map, items are stored densely and slots point into them
 #[derive(Debug, PartialEq)]
 pub struct List {
-    id: u32,
+    slots: Vec<Slot>,
     items: Vec<Item>,
+    free: Vec<u32>,
 }
 
 impl List {
     pub fn new() -> Self {
         Self {
-            id: 0,
+            slots: vec![],
             items: vec![],
+            free: vec![],
+        }
+    }
+    fn index(&self, id: u64) -> Option<usize> {
+        match self.slots.get(slot(id)) {
+            Some(slot) if slot.generation == generation(id) => slot.index,
+            _ => None,
         }
     }
     pub fn all(&self) -> &Vec<Item> {
         &self.items
     }
-    pub fn one(&self, id: u32) -> Option<&Item> {
-        self.items.iter().find(|item| item.id == id)
+    pub fn one(&self, id: u64) -> Option<&Item> {
+        self.index(id).map(|index| &self.items[index])
+    }
+    pub fn add(&mut self, name: &str, content: &str, active: bool) -> u64 {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    index: None,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let entry = &mut self.slots[slot as usize];
+        let id = id(slot, entry.generation);
+        entry.index = Some(self.items.len());
+        self.items.push(Item::new(id, name, content, active));
+        id
     }
-    pub fn add(&mut self, name: &str, content: &str, active: bool) {
-        self.id += 1;
-        self.items.push(Item::new(self.id, name, content, active))
+    pub fn update(&mut self, id: u64, name: &str, content: &str, active: bool) {
+        if let Some(index) = self.index(id) {
+            self.items[index].update(name, content, active);
+        }
     }
-    pub fn update(&mut self, id: u32, name: &str, content: &str, active: bool) {
-        for item in self.items.iter_mut() {
-            if item.id == id {
-                item.update(name, content, active);
+    pub fn remove(&mut self, id: u64) {
+        if let Some(index) = self.index(id) {
+            self.items.swap_remove(index);
+            if let Some(moved) = self.items.get(index) {
+                self.slots[slot(moved.id)].index = Some(index);
+            }
+            let entry = &mut self.slots[slot(id)];
+            entry.index = None;
+            entry.generation += 1;
+            if entry.generation < GENERATIONS {
+                self.free.push(slot(id) as u32);
             }
         }
     }
-    pub fn remove(&mut self, id: u32) {
-        self.items.retain(|item| item.id != id)
+    pub fn filter(&self, query: &Query<Item>) -> Vec<&Item> {
+        query.filter(&self.items)
+    }
+}
+
+impl Default for List {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -101,13 +197,19 @@ mod test {
     #[test]
     fn list_new() {
         let reference = List {
-            id: 0,
+            slots: vec![],
             items: vec![],
+            free: vec![],
         };
 
         assert_eq!(reference, List::new());
     }
 
+    #[test]
+    fn list_default() {
+        assert_eq!(List::new(), List::default());
+    }
+
     #[test]
     fn list_one() {
         let item = Item {
@@ -118,39 +220,66 @@ mod test {
         };
 
         let list = List {
-            id: 1,
+            slots: vec![
+                Slot {
+                    generation: 0,
+                    index: None,
+                },
+                Slot {
+                    generation: 0,
+                    index: Some(0),
+                },
+            ],
             items: vec![item.clone()],
+            free: vec![0],
         };
 
         assert_eq!(Some(&item), list.one(1));
+        assert_eq!(None, list.one(0));
+        assert_eq!(None, list.one(id(1, 1)));
+        assert_eq!(None, list.one(2));
     }
 
     #[test]
     fn list_all() {
         let item1 = Item {
-            id: 1,
+            id: 0,
             active: true,
             name: "name1".into(),
             content: "content1".into(),
         };
 
         let item2 = Item {
-            id: 2,
+            id: 1,
             active: true,
             name: "name2".into(),
             content: "content2".into(),
         };
 
         let item3 = Item {
-            id: 3,
+            id: 2,
             active: true,
             name: "name3".into(),
             content: "conten3".into(),
         };
 
         let list: List = List {
-            id: 3,
+            slots: vec![
+                Slot {
+                    generation: 0,
+                    index: Some(0),
+                },
+                Slot {
+                    generation: 0,
+                    index: Some(1),
+                },
+                Slot {
+                    generation: 0,
+                    index: Some(2),
+                },
+            ],
             items: vec![item1.clone(), item2.clone(), item3.clone()],
+            free: vec![],
         };
 
         assert_eq!(&vec![item1, item2, item3], list.all());
@@ -159,48 +288,57 @@ mod test {
     #[test]
     fn list_add() {
         let reference = List {
-            id: 1,
+            slots: vec![Slot {
+                generation: 0,
+                index: Some(0),
+            }],
             items: vec![Item {
-                id: 1,
+                id: 0,
                 active: true,
                 name: "name".into(),
                 content: "content".into(),
             }],
+            free: vec![],
         };
 
-        let mut list: List = List {
-            id: 0,
-            items: vec![],
-        };
-
-        list.add("name", "content", true);
+        let mut list = List::new();
 
+        assert_eq!(0, list.add("name", "content", true));
         assert_eq!(reference, list);
     }
 
     #[test]
     fn list_update() {
         let reference = List {
-            id: 1,
+            slots: vec![Slot {
+                generation: 0,
+                index: Some(0),
+            }],
             items: vec![Item {
-                id: 1,
+                id: 0,
                 active: true,
                 name: "name".into(),
                 content: "content".into(),
             }],
+            free: vec![],
         };
 
         let mut list = List {
-            id: 1,
+            slots: vec![Slot {
+                generation: 0,
+                index: Some(0),
+            }],
             items: vec![Item {
-                id: 1,
+                id: 0,
                 active: false,
                 name: "n".into(),
                 content: "c".into(),
             }],
+            free: vec![],
         };
 
-        list.update(1, "name", "content", true);
+        list.update(0, "name", "content", true);
+        list.update(id(0, 1), "stale", "stale", false);
 
         assert_eq!(reference, list);
     }
@@ -208,22 +346,91 @@ mod test {
     #[test]
     fn list_remove() {
         let reference = List {
-            id: 1,
+            slots: vec![Slot {
+                generation: 1,
+                index: None,
+            }],
             items: vec![],
+            free: vec![0],
         };
 
         let mut list = List {
-            id: 1,
+            slots: vec![Slot {
+                generation: 0,
+                index: Some(0),
+            }],
             items: vec![Item {
-                id: 1,
+                id: 0,
                 active: true,
                 name: "name".into(),
                 content: "content".into(),
             }],
+            free: vec![],
         };
 
-        list.remove(1);
+        list.remove(0);
+        list.remove(0);
 
         assert_eq!(reference, list)
     }
+
+    #[test]
+    fn list_remove_moves_last() {
+        let mut list = List::new();
+
+        let id1 = list.add("name1", "content1", true);
+        let id2 = list.add("name2", "content2", true);
+        let id3 = list.add("name3", "content3", true);
+
+        list.remove(id1);
+
+        assert_eq!(None, list.one(id1));
+        assert_eq!(Some("name2"), list.one(id2).map(Item::name));
+        assert_eq!(Some("name3"), list.one(id3).map(Item::name));
+        assert_eq!(
+            vec![id3, id2],
+            list.all().iter().map(Item::id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn list_reuse_slot() {
+        let mut list = List::new();
+
+        let old = list.add("old", "content", true);
+        list.remove(old);
+        let new = list.add("new", "content", true);
+
+        assert_eq!(slot(old), slot(new));
+        assert_ne!(old, new);
+        assert_eq!(None, list.one(old));
+        assert_eq!(Some("new"), list.one(new).map(Item::name));
+
+        list.remove(old);
+
+        assert_eq!(1, list.all().len());
+    }
+
+    #[test]
+    fn list_retire_slot() {
+        let mut list = List {
+            slots: vec![Slot {
+                generation: GENERATIONS - 1,
+                index: Some(0),
+            }],
+            items: vec![Item {
+                id: id(0, GENERATIONS - 1),
+                active: true,
+                name: "name".into(),
+                content: "content".into(),
+            }],
+            free: vec![],
+        };
+
+        list.remove(id(0, GENERATIONS - 1));
+        let id = list.add("name", "content", true);
+
+        assert_eq!(1, slot(id));
+        assert!(list.all().iter().all(|item| (item.id as i64) >= 0));
+    }
 }
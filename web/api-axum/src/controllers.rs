@@ -1,3 +1,4 @@
+use api_core::{Error, Input, Repository};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -5,66 +6,54 @@ use axum::{
     Json,
 };
 
-use crate::models::{Input, State as Store};
+type Failure = (StatusCode, String);
 
-pub async fn list(State(state): State<Store>) -> Result<impl IntoResponse, StatusCode> {
-    let items = state
-        .read()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .index();
+fn failure(error: Error) -> Failure {
+    let status = StatusCode::from_u16(error.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, error.to_string())
+}
+
+pub async fn list<R: Repository>(
+    State(repository): State<R>,
+) -> Result<impl IntoResponse, Failure> {
+    let items = repository.all().await.map_err(failure)?;
 
     Ok((StatusCode::OK, Json(items)))
 }
 
-pub async fn item(
+pub async fn item<R: Repository>(
     Path(id): Path<u32>,
-    State(state): State<Store>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let item = state
-        .read()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .get(id)
-        .ok_or(StatusCode::NOT_FOUND)?;
+    State(repository): State<R>,
+) -> Result<impl IntoResponse, Failure> {
+    let item = repository.get(id).await.map_err(failure)?;
 
     Ok((StatusCode::OK, Json(item)))
 }
 
-pub async fn add(
-    State(state): State<Store>,
+pub async fn add<R: Repository>(
+    State(repository): State<R>,
     Json(input): Json<Input>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let item = state
-        .write()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .insert(&input.name, &input.content, input.active)
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+) -> Result<impl IntoResponse, Failure> {
+    let item = repository.insert(input).await.map_err(failure)?;
 
     Ok((StatusCode::CREATED, Json(item)))
 }
 
-pub async fn update(
+pub async fn update<R: Repository>(
     Path(id): Path<u32>,
-    State(state): State<Store>,
+    State(repository): State<R>,
     Json(input): Json<Input>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let item = state
-        .write()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .update(id, &input.name, &input.content, input.active)
-        .ok_or(StatusCode::NOT_FOUND)?;
+) -> Result<impl IntoResponse, Failure> {
+    let item = repository.update(id, input).await.map_err(failure)?;
 
     Ok((StatusCode::OK, Json(item)))
 }
 
-pub async fn remove(
+pub async fn remove<R: Repository>(
     Path(id): Path<u32>,
-    State(state): State<Store>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let item = state
-        .write()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .remove(id)
-        .ok_or(StatusCode::NOT_FOUND)?;
+    State(repository): State<R>,
+) -> Result<impl IntoResponse, Failure> {
+    let item = repository.remove(id).await.map_err(failure)?;
 
     Ok((StatusCode::OK, Json(item)))
 }
@@ -1,12 +1,10 @@
+use api_core::Memory;
 use axum::{routing::get, Router};
-use std::sync::Arc;
 use tokio::net::TcpListener;
 
 use crate::controllers::{add, item, list, remove, update};
-use crate::models::State as Store;
 
 mod controllers;
-mod models;
 
 // http http://127.0.0.1:2986
 // http http://127.0.0.1:2986/1
@@ -18,12 +16,17 @@ mod models;
 async fn main() {
     let listener = TcpListener::bind("127.0.0.1:2986").await.unwrap();
 
-    let state = Store::default();
+    let repository = Memory::new();
 
     let app = Router::new()
-        .route("/", get(list).post(add))
-        .route("/{id}", get(item).put(update).delete(remove))
-        .with_state(Arc::clone(&state));
+        .route("/", get(list::<Memory>).post(add::<Memory>))
+        .route(
+            "/{id}",
+            get(item::<Memory>)
+                .put(update::<Memory>)
+                .delete(remove::<Memory>),
+        )
+        .with_state(repository);
 
     axum::serve(listener, app).await.unwrap();
 }
@@ -0,0 +1,31 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    NotFound,
+    Invalid(String),
+    Storage(String),
+}
+
+impl Error {
+    // HTTP status code the adapters answer with
+    pub fn status(&self) -> u16 {
+        match self {
+            Error::NotFound => 404,
+            Error::Invalid(_) => 400,
+            Error::Storage(_) => 500,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "Item not found"),
+            Error::Invalid(message) => write!(f, "Invalid item: {}", message),
+            Error::Storage(message) => write!(f, "Storage failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,9 @@
+pub mod error;
+pub mod memory;
+pub mod models;
+pub mod repository;
+
+pub use error::Error;
+pub use memory::Memory;
+pub use models::{Input, Item};
+pub use repository::Repository;
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::error::Error;
+use crate::models::{Input, Item};
+use crate::repository::Repository;
+
+#[derive(Default)]
+struct List {
+    id: u32,
+    items: BTreeMap<u32, Item>,
+}
+
+// Items in a shared map, the lock is never held across an await so any runtime works
+#[derive(Clone, Default)]
+pub struct Memory {
+    list: Arc<RwLock<List>>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn read(&self) -> Result<RwLockReadGuard<'_, List>, Error> {
+        self.list
+            .read()
+            .map_err(|_| Error::Storage("lock poisoned".into()))
+    }
+    fn write(&self) -> Result<RwLockWriteGuard<'_, List>, Error> {
+        self.list
+            .write()
+            .map_err(|_| Error::Storage("lock poisoned".into()))
+    }
+}
+
+impl Repository for Memory {
+    async fn all(&self) -> Result<Vec<Item>, Error> {
+        Ok(self.read()?.items.values().cloned().collect())
+    }
+    async fn get(&self, id: u32) -> Result<Item, Error> {
+        self.read()?.items.get(&id).cloned().ok_or(Error::NotFound)
+    }
+    async fn insert(&self, input: Input) -> Result<Item, Error> {
+        input.validate()?;
+        let mut list = self.write()?;
+        let id = list
+            .id
+            .checked_add(1)
+            .ok_or_else(|| Error::Storage("no ids left".into()))?;
+        list.id = id;
+        let item = Item::new(id, input);
+        list.items.insert(id, item.clone());
+        Ok(item)
+    }
+    async fn update(&self, id: u32, input: Input) -> Result<Item, Error> {
+        input.validate()?;
+        let mut list = self.write()?;
+        let item = list.items.get_mut(&id).ok_or(Error::NotFound)?;
+        Ok(item.update(input).clone())
+    }
+    async fn remove(&self, id: u32) -> Result<Item, Error> {
+        self.write()?.items.remove(&id).ok_or(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::MAX_NAME;
+    use futures::executor::block_on;
+
+    fn input(name: &str) -> Input {
+        Input {
+            active: true,
+            name: name.into(),
+            content: "content".into(),
+        }
+    }
+
+    #[test]
+    fn lifecycle() {
+        block_on(async {
+            let memory = Memory::new();
+            let first = memory.insert(input("first")).await.unwrap();
+            let second = memory.insert(input("second")).await.unwrap();
+            assert_eq!((first.id, second.id), (1, 2));
+            assert_eq!(memory.all().await.unwrap(), vec![first.clone(), second]);
+
+            let updated = memory.update(1, input("renamed")).await.unwrap();
+            assert_eq!(updated.name, "renamed");
+            assert_eq!(memory.get(1).await.unwrap(), updated);
+
+            assert_eq!(memory.remove(1).await.unwrap(), updated);
+            assert_eq!(memory.get(1).await, Err(Error::NotFound));
+            assert_eq!(memory.remove(1).await, Err(Error::NotFound));
+            assert_eq!(memory.insert(input("third")).await.unwrap().id, 3);
+        });
+    }
+
+    #[test]
+    fn validation() {
+        block_on(async {
+            let memory = Memory::new();
+            let long = "x".repeat(MAX_NAME + 1);
+            for name in ["", "  ", long.as_str()] {
+                let error = memory.insert(input(name)).await.unwrap_err();
+                assert!(matches!(error, Error::Invalid(_)), "{:?}", name);
+            }
+            memory.insert(input("valid")).await.unwrap();
+            assert!(matches!(
+                memory.update(1, input("")).await,
+                Err(Error::Invalid(_))
+            ));
+            assert_eq!(memory.update(9, input("valid")).await, Err(Error::NotFound));
+            assert_eq!(memory.all().await.unwrap().len(), 1);
+        });
+    }
+}
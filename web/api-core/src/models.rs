@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+pub const MAX_NAME: usize = 100;
+pub const MAX_CONTENT: usize = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Item {
+    pub id: u32,
+    pub active: bool,
+    pub name: String,
+    pub content: String,
+}
+
+impl Item {
+    pub fn new(id: u32, input: Input) -> Self {
+        Item {
+            id,
+            active: input.active,
+            name: input.name,
+            content: input.content,
+        }
+    }
+    pub fn update(&mut self, input: Input) -> &Self {
+        self.active = input.active;
+        self.name = input.name;
+        self.content = input.content;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Input {
+    pub active: bool,
+    pub name: String,
+    pub content: String,
+}
+
+impl Input {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Invalid("name must not be empty".into()));
+        }
+        if self.name.chars().count() > MAX_NAME {
+            return Err(Error::Invalid(format!(
+                "name must not be longer than {} characters",
+                MAX_NAME
+            )));
+        }
+        if self.content.chars().count() > MAX_CONTENT {
+            return Err(Error::Invalid(format!(
+                "content must not be longer than {} characters",
+                MAX_CONTENT
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+use std::future::Future;
+
+use crate::error::Error;
+use crate::models::{Input, Item};
+
+// Storage behind the HTTP adapters, inputs are validated before they are stored
+pub trait Repository: Clone + Send + Sync + 'static {
+    fn all(&self) -> impl Future<Output = Result<Vec<Item>, Error>> + Send;
+    fn get(&self, id: u32) -> impl Future<Output = Result<Item, Error>> + Send;
+    fn insert(&self, input: Input) -> impl Future<Output = Result<Item, Error>> + Send;
+    fn update(&self, id: u32, input: Input) -> impl Future<Output = Result<Item, Error>> + Send;
+    fn remove(&self, id: u32) -> impl Future<Output = Result<Item, Error>> + Send;
+}
@@ -1,51 +1,44 @@
+use api_core::{Input, Repository};
 use tide::{Request, StatusCode};
 
-use crate::models::{Input, List, State};
 use crate::utils::*;
 
-pub async fn all(request: Request<State<List>>) -> TideResult {
-    let list = get_list(&request);
-    let items = list.index();
-    json_response(&items)
+pub async fn all<R: Repository>(request: Request<R>) -> TideResult {
+    match request.state().all().await {
+        Ok(items) => json_response(StatusCode::Ok, &items),
+        Err(error) => failure(error),
+    }
 }
 
-pub async fn one(request: Request<State<List>>) -> TideResult {
+pub async fn one<R: Repository>(request: Request<R>) -> TideResult {
     let id = request.param("id")?.parse::<u32>()?;
-    let list = get_list(&request);
-    if let Some(item) = list.get(id) {
-        json_response(&item)
-    } else {
-        error(StatusCode::NotFound, ITEM_NOT_FOUND)
+    match request.state().get(id).await {
+        Ok(item) => json_response(StatusCode::Ok, &item),
+        Err(error) => failure(error),
     }
 }
 
-pub async fn add(mut request: Request<State<List>>) -> TideResult {
+pub async fn add<R: Repository>(mut request: Request<R>) -> TideResult {
     let input: Input = request.body_json().await?;
-    let mut list = get_list(&request);
-    if let Some(item) = list.insert(&input.name, &input.content, input.active) {
-        json_response(&item)
-    } else {
-        error(StatusCode::InternalServerError, SAVING_ITEM_FAILED)
+    match request.state().insert(input).await {
+        Ok(item) => json_response(StatusCode::Created, &item),
+        Err(error) => failure(error),
     }
 }
 
-pub async fn update(mut request: Request<State<List>>) -> TideResult {
+pub async fn update<R: Repository>(mut request: Request<R>) -> TideResult {
     let id = request.param("id")?.parse::<u32>()?;
     let input: Input = request.body_json().await?;
-    let mut list = get_list(&request);
-    if let Some(item) = list.update(id, &input.name, &input.content, input.active) {
-        json_response(&item)
-    } else {
-        error(StatusCode::NotFound, ITEM_NOT_FOUND)
+    match request.state().update(id, input).await {
+        Ok(item) => json_response(StatusCode::Ok, &item),
+        Err(error) => failure(error),
     }
 }
 
-pub async fn remove(request: Request<State<List>>) -> TideResult {
+pub async fn remove<R: Repository>(request: Request<R>) -> TideResult {
     let id = request.param("id")?.parse::<u32>()?;
-    let mut list = get_list(&request);
-    if let Some(item) = list.remove(id) {
-        json_response(&item)
-    } else {
-        error(StatusCode::NotFound, ITEM_NOT_FOUND)
+    match request.state().remove(id).await {
+        Ok(item) => json_response(StatusCode::Ok, &item),
+        Err(error) => failure(error),
     }
 }
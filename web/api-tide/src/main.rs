@@ -1,9 +1,8 @@
 mod controllers;
-mod models;
 mod utils;
 
+use api_core::Memory;
 use controllers::{add, all, one, remove, update};
-use models::State;
 
 // http http://127.0.0.1:8433
 // http http://127.0.0.1:8433/1
@@ -15,12 +14,15 @@ use models::State;
 async fn main() -> Result<(), std::io::Error> {
     tide::log::start();
 
-    let state = State::new();
+    let repository = Memory::new();
 
-    let mut app = tide::with_state(state);
+    let mut app = tide::with_state(repository);
 
-    app.at("/").get(all).post(add);
-    app.at("/:id").get(one).put(update).delete(remove);
+    app.at("/").get(all::<Memory>).post(add::<Memory>);
+    app.at("/:id")
+        .get(one::<Memory>)
+        .put(update::<Memory>)
+        .delete(remove::<Memory>);
 
     app.listen("127.0.0.1:8433").await?;
 
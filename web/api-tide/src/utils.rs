@@ -1,23 +1,14 @@
 use serde::Serialize;
 use serde_json::to_string;
-use std::sync::MutexGuard;
-use tide::{Error, Request, Response, StatusCode};
+use tide::{Error, Response, StatusCode};
 
-use crate::models::State;
-
-pub const ITEM_NOT_FOUND: &str = "Item not found";
-pub const SAVING_ITEM_FAILED: &str = "Saving item failed";
 pub const JSON_CONVERSION_FAILED: &str = "JSON conversion failed";
 
 pub type TideResult = tide::Result<Response>;
 
-pub fn get_list<'a, T: Default>(request: &'a Request<State<T>>) -> MutexGuard<'a, T> {
-    request.state().data.lock().unwrap()
-}
-
-pub fn json_response<T: Serialize>(data: &T) -> TideResult {
+pub fn json_response<T: Serialize>(status: StatusCode, data: &T) -> TideResult {
     if let Ok(json) = to_string(data) {
-        Ok(Response::builder(StatusCode::Ok)
+        Ok(Response::builder(status)
             .header("content-type", "application/json")
             .body(json)
             .build())
@@ -29,3 +20,8 @@ pub fn json_response<T: Serialize>(data: &T) -> TideResult {
 pub fn error(status: StatusCode, content: &'static str) -> TideResult {
     Err(Error::from_str(status, content))
 }
+
+pub fn failure(error: api_core::Error) -> TideResult {
+    let status = StatusCode::try_from(error.status()).unwrap_or(StatusCode::InternalServerError);
+    Err(Error::from_str(status, error.to_string()))
+}
@@ -1,58 +1,50 @@
 use std::convert::Infallible;
 
+use api_core::{Error, Input, Repository};
+use serde::Serialize;
 use warp::{
     http::StatusCode,
-    reply::{json, Response},
+    reply::{json, with_status, Response},
     Filter, Reply,
 };
 
-use crate::models::{Input, State};
+pub fn with<R: Repository>(
+    repository: R,
+) -> impl Filter<Extract = (R,), Error = Infallible> + Clone {
+    warp::any().map(move || repository.clone())
+}
 
-pub fn with(state: State) -> impl Filter<Extract = (State,), Error = Infallible> + Clone {
-    warp::any().map(move || state.clone())
+fn reply<T: Serialize>(result: Result<T, Error>, status: StatusCode) -> Response {
+    match result {
+        Ok(value) => with_status(json(&value), status).into_response(),
+        Err(error) => {
+            let status =
+                StatusCode::from_u16(error.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            with_status(error.to_string(), status).into_response()
+        }
+    }
 }
 
-pub async fn index(state: State) -> Result<impl Reply, Infallible> {
-    let list = state.lock().await;
-    Ok(json(&list.index()))
+pub async fn index<R: Repository>(repository: R) -> Result<Response, Infallible> {
+    Ok(reply(repository.all().await, StatusCode::OK))
 }
 
-pub async fn get(id: u32, state: State) -> Result<Response, Infallible> {
-    let list = state.lock().await;
-    if let Some(item) = list.get(id) {
-        Ok(json(&item).into_response())
-    } else {
-        Ok(StatusCode::NOT_FOUND.into_response())
-    }
+pub async fn get<R: Repository>(id: u32, repository: R) -> Result<Response, Infallible> {
+    Ok(reply(repository.get(id).await, StatusCode::OK))
 }
 
-pub async fn insert(input: Input, state: State) -> Result<Response, Infallible> {
-    let mut list = state.lock().await;
-    if let Some(item) = list.insert(&input.name, &input.content, input.active) {
-        Ok(json(&item).into_response())
-    } else {
-        Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response())
-    }
+pub async fn insert<R: Repository>(input: Input, repository: R) -> Result<Response, Infallible> {
+    Ok(reply(repository.insert(input).await, StatusCode::CREATED))
 }
 
-pub async fn update(id: u32, input: Input, state: State) -> Result<Response, Infallible> {
-    let mut list = state.lock().await;
-    if let Some(item) = list.get(id) {
-        let item = item.clone();
-        list.update(id, &input.name, &input.content, input.active);
-        Ok(json(&item).into_response())
-    } else {
-        Ok(StatusCode::NOT_FOUND.into_response())
-    }
+pub async fn update<R: Repository>(
+    id: u32,
+    input: Input,
+    repository: R,
+) -> Result<Response, Infallible> {
+    Ok(reply(repository.update(id, input).await, StatusCode::OK))
 }
 
-pub async fn remove(id: u32, state: State) -> Result<Response, Infallible> {
-    let mut list = state.lock().await;
-    if let Some(item) = list.get(id) {
-        let item = item.clone();
-        list.remove(id);
-        Ok(json(&item).into_response())
-    } else {
-        Ok(StatusCode::NOT_FOUND.into_response())
-    }
+pub async fn remove<R: Repository>(id: u32, repository: R) -> Result<Response, Infallible> {
+    Ok(reply(repository.remove(id).await, StatusCode::OK))
 }